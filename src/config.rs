@@ -3,9 +3,30 @@ use directories::ProjectDirs;
 use serde::Deserialize;
 use std::{path::PathBuf, time::Duration};
 
-#[derive(Deserialize, Debug, Clone, Default)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct RemoteDatabaseSettings {
     pub postgres_url: Option<String>,
+    #[serde(default = "default_connect_max_elapsed_secs")]
+    pub connect_max_elapsed_secs: u64,
+}
+
+fn default_connect_max_elapsed_secs() -> u64 {
+    60
+}
+
+impl Default for RemoteDatabaseSettings {
+    fn default() -> Self {
+        Self {
+            postgres_url: None,
+            connect_max_elapsed_secs: default_connect_max_elapsed_secs(),
+        }
+    }
+}
+
+impl RemoteDatabaseSettings {
+    pub fn connect_max_elapsed(&self) -> Duration {
+        Duration::from_secs(self.connect_max_elapsed_secs)
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -14,6 +35,14 @@ pub struct IntervalSettings {
     pub processing: u64,
     #[serde(default = "default_saving_interval")]
     pub saving: u64,
+    #[serde(default = "default_outbox_interval")]
+    pub outbox: u64,
+    /// Scales how long the reconciliation scrub worker rests between
+    /// batches: it sleeps `scrub_tranquility * batch_processing_time`, so
+    /// raising this linearly reduces the load scrubbing puts on both
+    /// databases at the cost of catching drift more slowly.
+    #[serde(default = "default_scrub_tranquility")]
+    pub scrub_tranquility: u32,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -24,6 +53,10 @@ pub struct Settings {
     pub intervals_ms: IntervalSettings,
     #[serde(default = "default_log_level")]
     pub log_level: String,
+    /// `"text"` (default, human-readable) or `"json"` (one structured
+    /// object per line, for downstream log tooling).
+    #[serde(default = "default_log_format")]
+    pub log_format: String,
 }
 
 // Default functions for serde
@@ -33,15 +66,26 @@ fn default_processing_interval() -> u64 {
 fn default_saving_interval() -> u64 {
     60000
 }
+fn default_outbox_interval() -> u64 {
+    30000
+}
+fn default_scrub_tranquility() -> u32 {
+    4
+}
 fn default_log_level() -> String {
     "info".to_string()
 }
+fn default_log_format() -> String {
+    "text".to_string()
+}
 
 impl Default for IntervalSettings {
     fn default() -> Self {
         Self {
             processing: default_processing_interval(),
             saving: default_saving_interval(),
+            outbox: default_outbox_interval(),
+            scrub_tranquility: default_scrub_tranquility(),
         }
     }
 }
@@ -49,12 +93,10 @@ impl Default for Settings {
     fn default() -> Self {
         Self {
             // local_database: LocalDatabaseSettings { path: default_local_db_path() }, // If configurable
-            database: RemoteDatabaseSettings { postgres_url: None },
-            intervals_ms: IntervalSettings {
-                processing: default_processing_interval(), // Ensure these defaults exist
-                saving: default_saving_interval(),
-            },
+            database: RemoteDatabaseSettings::default(),
+            intervals_ms: IntervalSettings::default(),
             log_level: default_log_level(),
+            log_format: default_log_format(),
         }
     }
 }
@@ -69,9 +111,16 @@ impl Settings {
 
         let builder = config::Config::builder()
             .set_default("database.postgres_url", None::<String>)?
+            .set_default(
+                "database.connect_max_elapsed_secs",
+                default_connect_max_elapsed_secs(),
+            )?
             .set_default("intervals_ms.processing", default_processing_interval())?
             .set_default("intervals_ms.saving", default_saving_interval())?
+            .set_default("intervals_ms.outbox", default_outbox_interval())?
+            .set_default("intervals_ms.scrub_tranquility", default_scrub_tranquility())?
             .set_default("log_level", default_log_level())?
+            .set_default("log_format", default_log_format())?
             .add_source(config::File::from(config_file).required(false))
             .add_source(config::Environment::with_prefix("ETSU").separator("__"));
 
@@ -101,4 +150,15 @@ impl Settings {
     pub fn saving_interval(&self) -> Duration {
         Duration::from_millis(self.intervals_ms.saving)
     }
+    pub fn outbox_interval(&self) -> Duration {
+        Duration::from_millis(self.intervals_ms.outbox)
+    }
+    pub fn scrub_tranquility(&self) -> u32 {
+        self.intervals_ms.scrub_tranquility
+    }
+    /// Whether `log_format` selects the structured JSON log layer, as
+    /// opposed to the default plain-text one.
+    pub fn log_format_is_json(&self) -> bool {
+        self.log_format.eq_ignore_ascii_case("json")
+    }
 }
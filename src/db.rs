@@ -3,16 +3,97 @@ use crate::error::Result;
 use sea_query::{Alias, Expr, Iden, PostgresQueryBuilder, Query, SimpleExpr, SqliteQueryBuilder};
 use sea_query_binder::SqlxBinder;
 use sqlx::{migrate::Migrator, Executor, PgPool, Pool, Postgres, Sqlite, SqlitePool, Transaction};
+use std::collections::HashMap;
+use std::io;
 use std::path::Path;
+use std::time::{Duration, Instant};
 use tracing::{debug, info, instrument, warn};
 
+const CONNECT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const CONNECT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// An outbox row older than this by the time it's drained is treated as a
+/// backlog replay rather than a just-happened interval: either it's a
+/// `scrub::ScrubWorker` repair of historical drift (always stale by
+/// construction), or an ordinary interval that piled up in `pg_outbox`
+/// during a remote outage and is only syncing now that the connection is
+/// back. Either way it shouldn't show up as a live spike on other devices,
+/// so `fetch_pending_outbox` marks it non-live and the Postgres writer skips
+/// `pg_notify` for it.
+const LIVE_NOTIFY_WINDOW_SQLITE: &str = "-30 seconds";
+
+/// Returns true if a Postgres error is transient and worth retrying (the
+/// server is temporarily unreachable), as opposed to a permanent problem
+/// (bad credentials, unknown host, constraint violation, etc.). Used both
+/// when establishing the initial connection and when replaying queued work
+/// against the remote pool.
+pub(crate) fn is_transient_error(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::Io(io_err) => matches!(
+            io_err.kind(),
+            io::ErrorKind::ConnectionRefused
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+        ),
+        _ => false,
+    }
+}
+
+/// Attempts to connect to Postgres, retrying transient failures with
+/// exponential backoff until `max_elapsed` has passed.
+pub(crate) async fn connect_postgres_with_backoff(
+    url: &str,
+    max_elapsed: Duration,
+) -> Option<Pool<Postgres>> {
+    let start = Instant::now();
+    let mut backoff = CONNECT_INITIAL_BACKOFF;
+
+    loop {
+        match PgPool::connect(url).await {
+            Ok(pool) => return Some(pool),
+            Err(e) if is_transient_error(&e) => {
+                if start.elapsed() >= max_elapsed {
+                    warn!(
+                        "Giving up connecting to remote Postgres after {:?}: {}. Remote sync will be disabled.",
+                        start.elapsed(),
+                        e
+                    );
+                    return None;
+                }
+                warn!(
+                    "Transient error connecting to remote Postgres: {}. Retrying in {:?}...",
+                    e, backoff
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(CONNECT_MAX_BACKOFF);
+            }
+            Err(e) => {
+                warn!(
+                    "Permanent error connecting to remote Postgres: {}. Remote sync will be disabled.",
+                    e
+                );
+                return None;
+            }
+        }
+    }
+}
+
+// Note for anyone bisecting migration history: `0002_add_metrics_synced_at`
+// and the `pg_outbox` table added in sqlite's `0005_add_pg_outbox` (dropping
+// `synced_at` in the same migration) are the same durable-outbox feature
+// built twice. `0002`'s `synced_at`-column design was superseded by the
+// separate outbox-table design before it ever shipped a working sync path,
+// so its schema is intentionally dead by the time `0005` runs. Already-run
+// migrations aren't edited after the fact, so this note lives here instead.
 static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
 static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
 
 #[derive(Iden)]
 #[iden = "metrics"]
-enum MetricsIden {
+pub(crate) enum MetricsIden {
     Table,
+    // No query builder references this column yet; kept as schema
+    // documentation rather than deleted as dead code.
     #[allow(dead_code)]
     Id,
     Keypresses,
@@ -20,7 +101,10 @@ enum MetricsIden {
     MouseDistanceIn,
     MouseDistanceMi,
     ScrollSteps,
-    #[allow(dead_code)]
+    ScrollUp,
+    ScrollDown,
+    ScrollLeft,
+    ScrollRight,
     Timestamp,
 }
 
@@ -45,6 +129,18 @@ pub struct MetricsData {
     pub mouse_distance_in: f64,
 }
 
+/// Signed scroll-wheel movement for an interval, persisted alongside
+/// `MetricsData` without changing its shape so existing consumers
+/// (`query`, `live`, the outbox) keep working against the rolled-up
+/// `scroll_steps` magnitude.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScrollDirections {
+    pub up: usize,
+    pub down: usize,
+    pub left: usize,
+    pub right: usize,
+}
+
 #[instrument(skip(remote_settings))]
 pub async fn setup_database_pools(
     local_db_path: &str,
@@ -67,16 +163,12 @@ pub async fn setup_database_pools(
     let pg_pool_option: Option<Pool<Postgres>> = match &remote_settings.postgres_url {
         Some(url) if !url.is_empty() => {
             info!("Setting up remote Postgres pool for URL...");
-            match PgPool::connect(url).await {
-                Ok(pool) => {
-                    info!("Remote Postgres pool created.");
-                    Some(pool)
-                }
-                Err(e) => {
-                    warn!("Failed to connect to remote Postgres DB: {}. Remote sync will be disabled.", e);
-                    None
-                }
+            let pool =
+                connect_postgres_with_backoff(url, remote_settings.connect_max_elapsed()).await;
+            if pool.is_some() {
+                info!("Remote Postgres pool created.");
             }
+            pool
         }
         _ => {
             info!("No remote Postgres URL configured.");
@@ -87,30 +179,156 @@ pub async fn setup_database_pools(
     Ok((sqlite_pool, pg_pool_option))
 }
 
+/// Runs migrations against both databases. The local SQLite pool is taken
+/// and returned by value because recovering from an incompatible on-disk
+/// schema (see [`run_sqlite_migrations_with_recovery`]) quarantines the old
+/// file and opens a fresh pool in its place — callers must use the pool
+/// this function returns, not the one they passed in.
 #[instrument(skip(sqlite_pool, pg_pool_option))]
 pub async fn run_migrations(
-    sqlite_pool: &Pool<Sqlite>,
+    sqlite_pool: Pool<Sqlite>,
+    local_db_path: &str,
     pg_pool_option: &Option<Pool<Postgres>>,
-) -> Result<()> {
+) -> Result<Pool<Sqlite>> {
     info!("Running database migrations...");
 
-    info!("Running migrations on local SQLite DB...");
-    SQLITE_MIGRATOR.run(sqlite_pool).await?;
+    let sqlite_pool = run_sqlite_migrations_with_recovery(sqlite_pool, local_db_path).await?;
     info!("Local SQLite migrations completed.");
 
     if let Some(pg_pool) = pg_pool_option {
-        info!("Running migrations on remote Postgres DB...");
-        match POSTGRES_MIGRATOR.run(pg_pool).await {
-            Ok(_) => info!("Remote Postgres migrations completed."),
-            Err(e) => {
-                warn!(
-                    "Failed to run migrations on remote Postgres DB: {}. Remote sync might fail.",
-                    e
-                );
+        run_postgres_migrations(pg_pool).await;
+    }
+    Ok(sqlite_pool)
+}
+
+/// Runs the SQLite migrator, recovering if the on-disk file's applied
+/// migrations are incompatible with this binary's (e.g. a dirty/partial
+/// migration left by a crashed older build, or a schema version this
+/// binary doesn't recognize). Rather than failing startup outright, the
+/// incompatible file is quarantined aside and a fresh one is created and
+/// migrated from scratch in its place.
+async fn run_sqlite_migrations_with_recovery(
+    pool: Pool<Sqlite>,
+    local_db_path: &str,
+) -> Result<Pool<Sqlite>> {
+    info!("Running migrations on local SQLite DB...");
+    match SQLITE_MIGRATOR.run(&pool).await {
+        Ok(()) => {
+            if let Ok(Some(version)) = schema_version_sqlite(&pool).await {
+                info!("Local SQLite schema is at version {}.", version);
             }
+            Ok(pool)
+        }
+        Err(e) if is_incompatible_schema_error(&e) => {
+            warn!(
+                "Local SQLite schema is incompatible with this build ({}); quarantining it and starting fresh.",
+                e
+            );
+            pool.close().await;
+            quarantine_sqlite_file(local_db_path).await?;
+
+            let fresh_pool = SqlitePool::connect_with(
+                sqlx::sqlite::SqliteConnectOptions::new()
+                    .filename(local_db_path)
+                    .create_if_missing(true),
+            )
+            .await?;
+            SQLITE_MIGRATOR.run(&fresh_pool).await?;
+            info!("Migrated fresh local SQLite DB after quarantining the incompatible one.");
+            Ok(fresh_pool)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Whether a migration failure means the on-disk schema is incompatible
+/// with this binary's migration set, as opposed to a transient I/O error.
+/// Matched on message content rather than the error's variant shape, since
+/// that shape isn't worth pinning to one `sqlx` version.
+fn is_incompatible_schema_error(err: &sqlx::migrate::MigrateError) -> bool {
+    let msg = err.to_string().to_lowercase();
+    msg.contains("dirty") || msg.contains("mismatch") || msg.contains("missing")
+}
+
+/// Moves an incompatible SQLite file aside into `<parent>/quarantine/<n>-<filename>`,
+/// picking the next unused `n`, so recovery never clobbers a previous
+/// quarantined file.
+async fn quarantine_sqlite_file(db_path: &str) -> Result<()> {
+    let path = Path::new(db_path);
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    let filename = path.file_name().and_then(|f| f.to_str()).unwrap_or("etsu.db");
+
+    let quarantine_dir = parent.join("quarantine");
+    tokio::fs::create_dir_all(&quarantine_dir).await?;
+
+    let mut n = 1u32;
+    loop {
+        let candidate = quarantine_dir.join(format!("{}-{}", n, filename));
+        if !candidate.exists() {
+            tokio::fs::rename(path, &candidate).await?;
+            warn!(
+                "Quarantined incompatible SQLite DB file '{}' to '{}'.",
+                db_path,
+                candidate.display()
+            );
+            return Ok(());
+        }
+        n += 1;
+    }
+}
+
+/// Returns the highest schema version recorded by `sqlx`'s migration
+/// bookkeeping table, or `None` if no migrations have been applied yet.
+#[instrument(skip(pool))]
+pub async fn schema_version_sqlite(pool: &Pool<Sqlite>) -> Result<Option<i64>> {
+    let row_opt = sqlx::query("SELECT MAX(version) AS version FROM _sqlx_migrations")
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(match row_opt {
+        Some(r) => {
+            use sqlx::Row;
+            r.try_get("version").ok()
+        }
+        None => None,
+    })
+}
+
+/// Opens an isolated, fully-migrated in-memory SQLite pool for tests. Uses
+/// a single-connection pool with a private (non-shared-cache) in-memory
+/// database, so each call gets its own clean database that disappears when
+/// the pool is dropped — no on-disk file, no cross-test interference.
+#[cfg(test)]
+pub(crate) async fn setup_inmemory_sqlite_pool() -> Result<Pool<Sqlite>> {
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(
+            sqlx::sqlite::SqliteConnectOptions::new()
+                .filename(":memory:")
+                .create_if_missing(true),
+        )
+        .await?;
+
+    SQLITE_MIGRATOR.run(&pool).await?;
+    Ok(pool)
+}
+
+/// Runs the Postgres migrator against `pool`, logging (rather than failing)
+/// on error so a migration hiccup doesn't take down remote sync entirely.
+/// Used both at startup and whenever the health monitor re-establishes the
+/// remote pool after an outage.
+#[instrument(skip(pool))]
+pub(crate) async fn run_postgres_migrations(pool: &Pool<Postgres>) {
+    info!("Running migrations on remote Postgres DB...");
+    match POSTGRES_MIGRATOR.run(pool).await {
+        Ok(_) => info!("Remote Postgres migrations completed."),
+        Err(e) => {
+            warn!(
+                "Failed to run migrations on remote Postgres DB: {}. Remote sync might fail.",
+                e
+            );
         }
     }
-    Ok(())
 }
 
 #[instrument(skip(pool))]
@@ -355,6 +573,7 @@ where
 async fn persist_metrics_sqlite_in_tx(
     tx: &mut Transaction<'_, Sqlite>,
     data: &MetricsData,
+    scroll: &ScrollDirections,
 ) -> Result<()> {
     let distance_mi = data.mouse_distance_in / 63360.0;
 
@@ -365,6 +584,10 @@ async fn persist_metrics_sqlite_in_tx(
             MetricsIden::Keypresses,
             MetricsIden::MouseClicks,
             MetricsIden::ScrollSteps,
+            MetricsIden::ScrollUp,
+            MetricsIden::ScrollDown,
+            MetricsIden::ScrollLeft,
+            MetricsIden::ScrollRight,
             MetricsIden::MouseDistanceIn,
             MetricsIden::MouseDistanceMi,
         ])
@@ -372,6 +595,10 @@ async fn persist_metrics_sqlite_in_tx(
             (data.keypresses as i64).into(),
             (data.mouse_clicks as i64).into(),
             (data.scroll_steps as i64).into(),
+            (scroll.up as i64).into(),
+            (scroll.down as i64).into(),
+            (scroll.left as i64).into(),
+            (scroll.right as i64).into(),
             data.mouse_distance_in.into(),
             distance_mi.into(),
         ]);
@@ -388,26 +615,52 @@ async fn persist_metrics_sqlite_in_tx(
 async fn persist_metrics_postgres_in_tx(
     tx: &mut Transaction<'_, Postgres>,
     data: &MetricsData,
+    scroll: &ScrollDirections,
+    bucket_date: Option<&str>,
+    notify_live: bool,
 ) -> Result<()> {
     let distance_mi = data.mouse_distance_in / 63360.0;
 
+    let mut columns = vec![
+        MetricsIden::Keypresses,
+        MetricsIden::MouseClicks,
+        MetricsIden::ScrollSteps,
+        MetricsIden::ScrollUp,
+        MetricsIden::ScrollDown,
+        MetricsIden::ScrollLeft,
+        MetricsIden::ScrollRight,
+        MetricsIden::MouseDistanceIn,
+        MetricsIden::MouseDistanceMi,
+    ];
+    let mut values: Vec<SimpleExpr> = vec![
+        (data.keypresses as i64).into(),
+        (data.mouse_clicks as i64).into(),
+        (data.scroll_steps as i64).into(),
+        (scroll.up as i64).into(),
+        (scroll.down as i64).into(),
+        (scroll.left as i64).into(),
+        (scroll.right as i64).into(),
+        data.mouse_distance_in.into(),
+        distance_mi.into(),
+    ];
+
+    // A scrub repair carries the day bucket it's repairing so the replayed
+    // row lands back on that day in Postgres instead of `DEFAULT now()` —
+    // otherwise a repair for a past day's drift would silently show up
+    // under today's bucket and the day-level totals would never converge.
+    if let Some(bucket) = bucket_date {
+        columns.push(MetricsIden::Timestamp);
+        values.push(Expr::cust_with_values(
+            "($1::date + interval '12 hours')::timestamptz",
+            [bucket],
+        ));
+    }
+
     let mut query_metrics = Query::insert();
     query_metrics
         .into_table(MetricsIden::Table)
-        .columns([
-            MetricsIden::Keypresses,
-            MetricsIden::MouseClicks,
-            MetricsIden::ScrollSteps,
-            MetricsIden::MouseDistanceIn,
-            MetricsIden::MouseDistanceMi,
-        ])
-        .values_panic([
-            (data.keypresses as i64).into(),
-            (data.mouse_clicks as i64).into(),
-            (data.scroll_steps as i64).into(),
-            data.mouse_distance_in.into(),
-            distance_mi.into(),
-        ]);
+        .columns(columns)
+        .values_panic(values);
     let (sql_metrics, values_metrics) = query_metrics.build_sqlx(PostgresQueryBuilder);
     sqlx::query_with(&sql_metrics, values_metrics)
         .execute(&mut **tx)
@@ -415,43 +668,70 @@ async fn persist_metrics_postgres_in_tx(
 
     update_summary_table_postgres(&mut **tx, data).await?;
 
+    // Only a genuinely-live interval should appear as a live spike on other
+    // devices. Scrub repairs and outbox backlog replayed after a remote
+    // outage both land here too, but notifying for those would make stale
+    // data look like it just happened — see `LIVE_NOTIFY_WINDOW_SQLITE`.
+    if notify_live {
+        notify_metrics_delta(&mut **tx, data).await?;
+    }
+
     Ok(())
 }
 
-#[instrument(skip(pool, data), fields(db_type = "sqlite"))]
-pub async fn persist_metrics_sqlite(pool: &Pool<Sqlite>, data: &MetricsData) -> Result<()> {
-    if data.keypresses == 0
-        && data.mouse_clicks == 0
-        && data.scroll_steps == 0
-        && data.mouse_distance_in == 0.0
-    {
-        return Ok(());
-    }
+/// Publishes the interval delta on `live::NOTIFY_CHANNEL` so other devices
+/// sharing this Postgres database can aggregate it live. Postgres defers
+/// delivery of `pg_notify` until the enclosing transaction commits, so a
+/// rolled-back write never notifies.
+async fn notify_metrics_delta<'c, E>(executor: E, data: &MetricsData) -> Result<()>
+where
+    E: Executor<'c, Database = sqlx::Postgres>,
+{
+    let delta = crate::live::MetricsDelta::from_data(data, crate::live::device_id());
+    let payload = serde_json::to_string(&delta).map_err(|e| {
+        warn!("Failed to serialize live metrics delta: {}", e);
+        sqlx::Error::Protocol(e.to_string())
+    })?;
+
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(crate::live::NOTIFY_CHANNEL)
+        .bind(payload)
+        .execute(executor)
+        .await?;
 
-    persist_metrics_transactional_sqlite(pool, data).await
+    Ok(())
 }
 
-#[instrument(skip(pool, data), fields(db_type = "postgres"))]
-pub async fn persist_metrics_postgres(pool: &Pool<Postgres>, data: &MetricsData) -> Result<()> {
+#[instrument(skip(pool, data, scroll), fields(db_type = "sqlite"))]
+pub async fn persist_metrics_sqlite(
+    pool: &Pool<Sqlite>,
+    data: &MetricsData,
+    scroll: &ScrollDirections,
+) -> Result<()> {
     if data.keypresses == 0
         && data.mouse_clicks == 0
         && data.scroll_steps == 0
         && data.mouse_distance_in == 0.0
+        && scroll.up == 0
+        && scroll.down == 0
+        && scroll.left == 0
+        && scroll.right == 0
     {
         return Ok(());
     }
 
-    persist_metrics_transactional_postgres(pool, data).await
+    persist_metrics_transactional_sqlite(pool, data, scroll).await
 }
 
-#[instrument(skip(pool, data), fields(db_type = "sqlite"))]
+#[instrument(skip(pool, data, scroll), fields(db_type = "sqlite"))]
 pub async fn persist_metrics_transactional_sqlite(
     pool: &Pool<Sqlite>,
     data: &MetricsData,
+    scroll: &ScrollDirections,
 ) -> Result<()> {
     let mut tx = pool.begin().await?;
-    let result = persist_metrics_sqlite_in_tx(&mut tx, data).await;
-    
+    let result = persist_metrics_sqlite_in_tx(&mut tx, data, scroll).await;
+
     match result {
         Ok(_) => {
             tx.commit().await?;
@@ -469,27 +749,813 @@ pub async fn persist_metrics_transactional_sqlite(
     }
 }
 
-#[instrument(skip(pool, data), fields(db_type = "postgres"))]
-pub async fn persist_metrics_transactional_postgres(
+/// Adds the given delta to each key's running count, inserting a new row
+/// the first time a key is seen.
+#[instrument(skip(pool, counts))]
+pub async fn persist_key_counts_sqlite(
+    pool: &Pool<Sqlite>,
+    counts: &HashMap<String, usize>,
+) -> Result<()> {
+    if counts.is_empty() {
+        return Ok(());
+    }
+
+    let mut tx = pool.begin().await?;
+    for (key_name, count) in counts {
+        sqlx::query(
+            "INSERT INTO key_counts (key_name, count) VALUES (?, ?) \
+             ON CONFLICT(key_name) DO UPDATE SET count = count + excluded.count",
+        )
+        .bind(key_name)
+        .bind(*count as i64)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Postgres counterpart of [`persist_key_counts_sqlite`].
+#[instrument(skip(pool, counts))]
+pub async fn persist_key_counts_postgres(
     pool: &Pool<Postgres>,
+    counts: &HashMap<String, usize>,
+) -> Result<()> {
+    if counts.is_empty() {
+        return Ok(());
+    }
+
+    let mut tx = pool.begin().await?;
+    for (key_name, count) in counts {
+        sqlx::query(
+            "INSERT INTO key_counts (key_name, count) VALUES ($1, $2) \
+             ON CONFLICT(key_name) DO UPDATE SET count = key_counts.count + excluded.count",
+        )
+        .bind(key_name)
+        .bind(*count as i64)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Adds the given delta to each mouse button's running count, inserting a
+/// new row the first time a button is seen.
+#[instrument(skip(pool, counts))]
+pub async fn persist_button_counts_sqlite(
+    pool: &Pool<Sqlite>,
+    counts: &HashMap<String, usize>,
+) -> Result<()> {
+    if counts.is_empty() {
+        return Ok(());
+    }
+
+    let mut tx = pool.begin().await?;
+    for (button_name, count) in counts {
+        sqlx::query(
+            "INSERT INTO button_counts (button_name, count) VALUES (?, ?) \
+             ON CONFLICT(button_name) DO UPDATE SET count = count + excluded.count",
+        )
+        .bind(button_name)
+        .bind(*count as i64)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Postgres counterpart of [`persist_button_counts_sqlite`].
+#[instrument(skip(pool, counts))]
+pub async fn persist_button_counts_postgres(
+    pool: &Pool<Postgres>,
+    counts: &HashMap<String, usize>,
+) -> Result<()> {
+    if counts.is_empty() {
+        return Ok(());
+    }
+
+    let mut tx = pool.begin().await?;
+    for (button_name, count) in counts {
+        sqlx::query(
+            "INSERT INTO button_counts (button_name, count) VALUES ($1, $2) \
+             ON CONFLICT(button_name) DO UPDATE SET count = button_counts.count + excluded.count",
+        )
+        .bind(button_name)
+        .bind(*count as i64)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[derive(Iden)]
+#[iden = "pg_outbox"]
+enum PgOutboxIden {
+    Table,
+    Id,
+    Keypresses,
+    MouseClicks,
+    ScrollSteps,
+    ScrollUp,
+    ScrollDown,
+    ScrollLeft,
+    ScrollRight,
+    MouseDistanceIn,
+    Synced,
+    RetryCount,
+    BucketDate,
+    CreatedAt,
+}
+
+/// A pending row in the local `pg_outbox` staging table, awaiting replay to
+/// the remote Postgres database.
+#[derive(Debug, Clone)]
+pub struct OutboxRow {
+    pub id: i64,
+    pub data: MetricsData,
+    pub scroll: ScrollDirections,
+    pub retry_count: i64,
+    /// Day bucket (`YYYY-MM-DD`) this row should be attributed to in
+    /// Postgres, set by `scrub::ScrubWorker`'s drift repair via
+    /// [`enqueue_outbox_for_bucket`]. `None` for ordinary interval rows from
+    /// [`enqueue_outbox`], which replay under Postgres's `DEFAULT now()`.
+    pub bucket_date: Option<String>,
+    /// Whether this row is a genuinely-live interval rather than a backlog
+    /// replay, per [`LIVE_NOTIFY_WINDOW_SQLITE`]. Threaded through to
+    /// [`persist_metrics_postgres_in_tx`] so only live rows trigger
+    /// `pg_notify`.
+    pub is_live: bool,
+}
+
+/// Appends one interval's metrics to the local `pg_outbox` staging table.
+/// This is the only way interval metrics reach Postgres: `outbox::OutboxSyncWorker`
+/// drains this table in the background, so a transient remote outage never
+/// loses the interval, only delays it.
+#[instrument(skip(pool, data, scroll))]
+pub async fn enqueue_outbox(
+    pool: &Pool<Sqlite>,
+    data: &MetricsData,
+    scroll: &ScrollDirections,
+) -> Result<()> {
+    let mut query = Query::insert();
+    query
+        .into_table(PgOutboxIden::Table)
+        .columns([
+            PgOutboxIden::Keypresses,
+            PgOutboxIden::MouseClicks,
+            PgOutboxIden::ScrollSteps,
+            PgOutboxIden::ScrollUp,
+            PgOutboxIden::ScrollDown,
+            PgOutboxIden::ScrollLeft,
+            PgOutboxIden::ScrollRight,
+            PgOutboxIden::MouseDistanceIn,
+        ])
+        .values_panic([
+            (data.keypresses as i64).into(),
+            (data.mouse_clicks as i64).into(),
+            (data.scroll_steps as i64).into(),
+            (scroll.up as i64).into(),
+            (scroll.down as i64).into(),
+            (scroll.left as i64).into(),
+            (scroll.right as i64).into(),
+            data.mouse_distance_in.into(),
+        ]);
+    let (sql, values) = query.build_sqlx(SqliteQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+
+    Ok(())
+}
+
+/// Like [`enqueue_outbox`], but stamps the row with `bucket_date` so
+/// `outbox::drain_batch` replays it into Postgres under that day's
+/// timestamp instead of `DEFAULT now()`. Used by `scrub::ScrubWorker` to
+/// repair drift in a specific day bucket without misattributing the
+/// repaired totals to the day the repair happened to run.
+#[instrument(skip(pool, data, scroll))]
+pub async fn enqueue_outbox_for_bucket(
+    pool: &Pool<Sqlite>,
     data: &MetricsData,
+    scroll: &ScrollDirections,
+    bucket_date: &str,
 ) -> Result<()> {
+    let mut query = Query::insert();
+    query
+        .into_table(PgOutboxIden::Table)
+        .columns([
+            PgOutboxIden::Keypresses,
+            PgOutboxIden::MouseClicks,
+            PgOutboxIden::ScrollSteps,
+            PgOutboxIden::ScrollUp,
+            PgOutboxIden::ScrollDown,
+            PgOutboxIden::ScrollLeft,
+            PgOutboxIden::ScrollRight,
+            PgOutboxIden::MouseDistanceIn,
+            PgOutboxIden::BucketDate,
+        ])
+        .values_panic([
+            (data.keypresses as i64).into(),
+            (data.mouse_clicks as i64).into(),
+            (data.scroll_steps as i64).into(),
+            (scroll.up as i64).into(),
+            (scroll.down as i64).into(),
+            (scroll.left as i64).into(),
+            (scroll.right as i64).into(),
+            data.mouse_distance_in.into(),
+            bucket_date.into(),
+        ]);
+    let (sql, values) = query.build_sqlx(SqliteQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+
+    Ok(())
+}
+
+/// Fetches up to `limit` pending (`synced = 0`) rows from `pg_outbox` with
+/// fewer than `max_retries` failed replay attempts, oldest first. Rows that
+/// have hit `max_retries` are excluded so one permanently-failing row can't
+/// block every row behind it in the same all-or-nothing batch; they stay in
+/// the table, unsynced, for an operator to inspect.
+#[instrument(skip(pool))]
+pub async fn fetch_pending_outbox(
+    pool: &Pool<Sqlite>,
+    limit: u64,
+    max_retries: i64,
+) -> Result<Vec<OutboxRow>> {
+    let is_live = Expr::col(PgOutboxIden::BucketDate).is_null().and(
+        Expr::col(PgOutboxIden::CreatedAt).gte(Expr::cust_with_values(
+            "datetime('now', ?)",
+            [LIVE_NOTIFY_WINDOW_SQLITE],
+        )),
+    );
+    let query = Query::select()
+        .columns([
+            PgOutboxIden::Id,
+            PgOutboxIden::Keypresses,
+            PgOutboxIden::MouseClicks,
+            PgOutboxIden::ScrollSteps,
+            PgOutboxIden::ScrollUp,
+            PgOutboxIden::ScrollDown,
+            PgOutboxIden::ScrollLeft,
+            PgOutboxIden::ScrollRight,
+            PgOutboxIden::MouseDistanceIn,
+            PgOutboxIden::RetryCount,
+            PgOutboxIden::BucketDate,
+        ])
+        .expr_as(is_live, Alias::new("is_live"))
+        .from(PgOutboxIden::Table)
+        .and_where(Expr::col(PgOutboxIden::Synced).eq(0))
+        .and_where(Expr::col(PgOutboxIden::RetryCount).lt(max_retries))
+        .order_by(PgOutboxIden::Id, sea_query::Order::Asc)
+        .limit(limit)
+        .to_owned();
+
+    let (sql, values) = query.build_sqlx(SqliteQueryBuilder);
+
+    use sqlx::Row;
+    let rows = sqlx::query_with(&sql, values).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            let keys: i64 = r.try_get("keypresses").unwrap_or(0);
+            let clicks: i64 = r.try_get("mouse_clicks").unwrap_or(0);
+            let scrolls: i64 = r.try_get("scroll_steps").unwrap_or(0);
+            let scroll_up: i64 = r.try_get("scroll_up").unwrap_or(0);
+            let scroll_down: i64 = r.try_get("scroll_down").unwrap_or(0);
+            let scroll_left: i64 = r.try_get("scroll_left").unwrap_or(0);
+            let scroll_right: i64 = r.try_get("scroll_right").unwrap_or(0);
+            let distance: f64 = r.try_get("mouse_distance_in").unwrap_or(0.0);
+            OutboxRow {
+                id: r.try_get("id").unwrap_or_default(),
+                data: MetricsData {
+                    keypresses: keys as usize,
+                    mouse_clicks: clicks as usize,
+                    scroll_steps: scrolls as usize,
+                    mouse_distance_in: distance,
+                },
+                scroll: ScrollDirections {
+                    up: scroll_up as usize,
+                    down: scroll_down as usize,
+                    left: scroll_left as usize,
+                    right: scroll_right as usize,
+                },
+                retry_count: r.try_get("retry_count").unwrap_or(0),
+                bucket_date: r.try_get("bucket_date").unwrap_or_default(),
+                is_live: r.try_get("is_live").unwrap_or(false),
+            }
+        })
+        .collect())
+}
+
+/// Marks the given `pg_outbox` rows synced now that they've been confirmed
+/// persisted in Postgres.
+#[instrument(skip(pool))]
+pub async fn mark_outbox_synced(pool: &Pool<Sqlite>, ids: &[i64]) -> Result<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    let query = Query::update()
+        .table(PgOutboxIden::Table)
+        .value(PgOutboxIden::Synced, 1_i64)
+        .and_where(Expr::col(PgOutboxIden::Id).is_in(ids.iter().copied()))
+        .to_owned();
+
+    let (sql, values) = query.build_sqlx(SqliteQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+
+    Ok(())
+}
+
+/// Bumps the retry count on the given `pg_outbox` rows after a failed replay
+/// attempt.
+#[instrument(skip(pool))]
+pub async fn increment_outbox_retry(pool: &Pool<Sqlite>, ids: &[i64]) -> Result<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    let query = Query::update()
+        .table(PgOutboxIden::Table)
+        .value(
+            PgOutboxIden::RetryCount,
+            Expr::col(PgOutboxIden::RetryCount).add(1),
+        )
+        .and_where(Expr::col(PgOutboxIden::Id).is_in(ids.iter().copied()))
+        .to_owned();
+
+    let (sql, values) = query.build_sqlx(SqliteQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+
+    Ok(())
+}
+
+/// Replays a whole batch of outbox rows to Postgres in a single transaction:
+/// either every row lands (and each one's summary update, plus the live
+/// notify for rows still flagged live, fires) or none do, so a mid-batch
+/// failure never leaves Postgres partially ahead of what `pg_outbox` still
+/// thinks is pending.
+#[instrument(skip(pool, rows), fields(db_type = "postgres"))]
+pub async fn persist_metrics_batch_postgres(
+    pool: &Pool<Postgres>,
+    rows: &[(MetricsData, ScrollDirections, Option<String>, bool)],
+) -> Result<()> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+
     let mut tx = pool.begin().await?;
-    let result = persist_metrics_postgres_in_tx(&mut tx, data).await;
-    
-    match result {
-        Ok(_) => {
-            tx.commit().await?;
-            debug!(
-                "Postgres transaction committed for metrics interval: {:?}",
-                data
-            );
-            Ok(())
-        }
-        Err(e) => {
-            warn!("Postgres transaction failed, rolling back: {}", e);
+    for (data, scroll, bucket_date, is_live) in rows {
+        if let Err(e) =
+            persist_metrics_postgres_in_tx(&mut tx, data, scroll, bucket_date.as_deref(), *is_live)
+                .await
+        {
+            warn!("Postgres outbox batch failed, rolling back: {}", e);
             let _ = tx.rollback().await;
-            Err(e)
+            return Err(e);
         }
     }
+    tx.commit().await?;
+    debug!(
+        "Postgres transaction committed for outbox batch of {} row(s).",
+        rows.len()
+    );
+
+    Ok(())
+}
+
+#[derive(Iden)]
+#[iden = "key_count_outbox"]
+enum KeyCountOutboxIden {
+    Table,
+    Id,
+    KeyName,
+    Delta,
+    Synced,
+    RetryCount,
+}
+
+#[derive(Iden)]
+#[iden = "button_count_outbox"]
+enum ButtonCountOutboxIden {
+    Table,
+    Id,
+    ButtonName,
+    Delta,
+    Synced,
+    RetryCount,
+}
+
+/// A pending per-key/button count delta awaiting replay to Postgres.
+#[derive(Debug, Clone)]
+pub struct OutboxCountRow {
+    pub id: i64,
+    pub name: String,
+    pub delta: i64,
+    pub retry_count: i64,
+}
+
+/// Appends one delta per key to the local `key_count_outbox` staging table.
+/// Mirrors [`enqueue_outbox`]: this is the only way key-count deltas reach
+/// Postgres, so a transient remote outage only delays them rather than
+/// losing them.
+#[instrument(skip(pool, counts))]
+pub async fn enqueue_key_count_outbox(
+    pool: &Pool<Sqlite>,
+    counts: &HashMap<String, usize>,
+) -> Result<()> {
+    if counts.is_empty() {
+        return Ok(());
+    }
+
+    let mut tx = pool.begin().await?;
+    for (key_name, count) in counts {
+        let query = Query::insert()
+            .into_table(KeyCountOutboxIden::Table)
+            .columns([KeyCountOutboxIden::KeyName, KeyCountOutboxIden::Delta])
+            .values_panic([key_name.as_str().into(), (*count as i64).into()])
+            .to_owned();
+        let (sql, values) = query.build_sqlx(SqliteQueryBuilder);
+        sqlx::query_with(&sql, values).execute(&mut *tx).await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Fetches up to `limit` pending (`synced = 0`) rows from `key_count_outbox`
+/// with fewer than `max_retries` failed replay attempts, oldest first. See
+/// [`fetch_pending_outbox`] for why rows past the threshold are excluded.
+#[instrument(skip(pool))]
+pub async fn fetch_pending_key_count_outbox(
+    pool: &Pool<Sqlite>,
+    limit: u64,
+    max_retries: i64,
+) -> Result<Vec<OutboxCountRow>> {
+    let query = Query::select()
+        .columns([
+            KeyCountOutboxIden::Id,
+            KeyCountOutboxIden::KeyName,
+            KeyCountOutboxIden::Delta,
+            KeyCountOutboxIden::RetryCount,
+        ])
+        .from(KeyCountOutboxIden::Table)
+        .and_where(Expr::col(KeyCountOutboxIden::Synced).eq(0))
+        .and_where(Expr::col(KeyCountOutboxIden::RetryCount).lt(max_retries))
+        .order_by(KeyCountOutboxIden::Id, sea_query::Order::Asc)
+        .limit(limit)
+        .to_owned();
+    let (sql, values) = query.build_sqlx(SqliteQueryBuilder);
+
+    use sqlx::Row;
+    let rows = sqlx::query_with(&sql, values).fetch_all(pool).await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| OutboxCountRow {
+            id: r.try_get("id").unwrap_or_default(),
+            name: r.try_get("key_name").unwrap_or_default(),
+            delta: r.try_get("delta").unwrap_or(0),
+            retry_count: r.try_get("retry_count").unwrap_or(0),
+        })
+        .collect())
+}
+
+/// Marks the given `key_count_outbox` rows synced now that they've been
+/// confirmed persisted in Postgres.
+#[instrument(skip(pool))]
+pub async fn mark_key_count_outbox_synced(pool: &Pool<Sqlite>, ids: &[i64]) -> Result<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    let query = Query::update()
+        .table(KeyCountOutboxIden::Table)
+        .value(KeyCountOutboxIden::Synced, 1_i64)
+        .and_where(Expr::col(KeyCountOutboxIden::Id).is_in(ids.iter().copied()))
+        .to_owned();
+    let (sql, values) = query.build_sqlx(SqliteQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+
+    Ok(())
+}
+
+/// Bumps the retry count on the given `key_count_outbox` rows after a failed
+/// replay attempt.
+#[instrument(skip(pool))]
+pub async fn increment_key_count_outbox_retry(pool: &Pool<Sqlite>, ids: &[i64]) -> Result<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    let query = Query::update()
+        .table(KeyCountOutboxIden::Table)
+        .value(
+            KeyCountOutboxIden::RetryCount,
+            Expr::col(KeyCountOutboxIden::RetryCount).add(1),
+        )
+        .and_where(Expr::col(KeyCountOutboxIden::Id).is_in(ids.iter().copied()))
+        .to_owned();
+    let (sql, values) = query.build_sqlx(SqliteQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+
+    Ok(())
+}
+
+/// Mouse-button counterpart of [`enqueue_key_count_outbox`], staging deltas
+/// in the local `button_count_outbox` table.
+#[instrument(skip(pool, counts))]
+pub async fn enqueue_button_count_outbox(
+    pool: &Pool<Sqlite>,
+    counts: &HashMap<String, usize>,
+) -> Result<()> {
+    if counts.is_empty() {
+        return Ok(());
+    }
+
+    let mut tx = pool.begin().await?;
+    for (button_name, count) in counts {
+        let query = Query::insert()
+            .into_table(ButtonCountOutboxIden::Table)
+            .columns([ButtonCountOutboxIden::ButtonName, ButtonCountOutboxIden::Delta])
+            .values_panic([button_name.as_str().into(), (*count as i64).into()])
+            .to_owned();
+        let (sql, values) = query.build_sqlx(SqliteQueryBuilder);
+        sqlx::query_with(&sql, values).execute(&mut *tx).await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Fetches up to `limit` pending (`synced = 0`) rows from
+/// `button_count_outbox` with fewer than `max_retries` failed replay
+/// attempts, oldest first. See [`fetch_pending_outbox`] for why rows past
+/// the threshold are excluded.
+#[instrument(skip(pool))]
+pub async fn fetch_pending_button_count_outbox(
+    pool: &Pool<Sqlite>,
+    limit: u64,
+    max_retries: i64,
+) -> Result<Vec<OutboxCountRow>> {
+    let query = Query::select()
+        .columns([
+            ButtonCountOutboxIden::Id,
+            ButtonCountOutboxIden::ButtonName,
+            ButtonCountOutboxIden::Delta,
+            ButtonCountOutboxIden::RetryCount,
+        ])
+        .from(ButtonCountOutboxIden::Table)
+        .and_where(Expr::col(ButtonCountOutboxIden::Synced).eq(0))
+        .and_where(Expr::col(ButtonCountOutboxIden::RetryCount).lt(max_retries))
+        .order_by(ButtonCountOutboxIden::Id, sea_query::Order::Asc)
+        .limit(limit)
+        .to_owned();
+    let (sql, values) = query.build_sqlx(SqliteQueryBuilder);
+
+    use sqlx::Row;
+    let rows = sqlx::query_with(&sql, values).fetch_all(pool).await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| OutboxCountRow {
+            id: r.try_get("id").unwrap_or_default(),
+            name: r.try_get("button_name").unwrap_or_default(),
+            delta: r.try_get("delta").unwrap_or(0),
+            retry_count: r.try_get("retry_count").unwrap_or(0),
+        })
+        .collect())
+}
+
+/// Marks the given `button_count_outbox` rows synced now that they've been
+/// confirmed persisted in Postgres.
+#[instrument(skip(pool))]
+pub async fn mark_button_count_outbox_synced(pool: &Pool<Sqlite>, ids: &[i64]) -> Result<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    let query = Query::update()
+        .table(ButtonCountOutboxIden::Table)
+        .value(ButtonCountOutboxIden::Synced, 1_i64)
+        .and_where(Expr::col(ButtonCountOutboxIden::Id).is_in(ids.iter().copied()))
+        .to_owned();
+    let (sql, values) = query.build_sqlx(SqliteQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+
+    Ok(())
+}
+
+/// Bumps the retry count on the given `button_count_outbox` rows after a
+/// failed replay attempt.
+#[instrument(skip(pool))]
+pub async fn increment_button_count_outbox_retry(pool: &Pool<Sqlite>, ids: &[i64]) -> Result<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    let query = Query::update()
+        .table(ButtonCountOutboxIden::Table)
+        .value(
+            ButtonCountOutboxIden::RetryCount,
+            Expr::col(ButtonCountOutboxIden::RetryCount).add(1),
+        )
+        .and_where(Expr::col(ButtonCountOutboxIden::Id).is_in(ids.iter().copied()))
+        .to_owned();
+    let (sql, values) = query.build_sqlx(SqliteQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+
+    Ok(())
+}
+
+#[derive(Iden)]
+#[iden = "scrub_state"]
+enum ScrubStateIden {
+    Table,
+    Id,
+    LastBucket,
+}
+
+/// Returns the day bucket (`YYYY-MM-DD`) the reconciliation scrub worker
+/// last finished, or an empty string if it hasn't scrubbed anything yet.
+#[instrument(skip(pool))]
+pub async fn get_scrub_position(pool: &Pool<Sqlite>) -> Result<String> {
+    let query = Query::select()
+        .column(ScrubStateIden::LastBucket)
+        .from(ScrubStateIden::Table)
+        .and_where(Expr::col(ScrubStateIden::Id).eq(1))
+        .limit(1)
+        .to_owned();
+    let (sql, values) = query.build_sqlx(SqliteQueryBuilder);
+
+    let row_opt = sqlx::query_with(&sql, values).fetch_optional(pool).await?;
+    match row_opt {
+        Some(r) => {
+            use sqlx::Row;
+            Ok(r.try_get("last_bucket").unwrap_or_default())
+        }
+        None => Ok(String::new()),
+    }
+}
+
+/// Persists `bucket` as the scrub worker's last-completed position, so a
+/// restart resumes scanning forward from there instead of rescanning
+/// history it already reconciled.
+#[instrument(skip(pool))]
+pub async fn set_scrub_position(pool: &Pool<Sqlite>, bucket: &str) -> Result<()> {
+    let query = Query::update()
+        .table(ScrubStateIden::Table)
+        .value(ScrubStateIden::LastBucket, bucket)
+        .and_where(Expr::col(ScrubStateIden::Id).eq(1))
+        .to_owned();
+    let (sql, values) = query.build_sqlx(SqliteQueryBuilder);
+    sqlx::query_with(&sql, values).execute(pool).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn persist_metrics_sqlite_updates_summary_and_metrics_table() {
+        let pool = setup_inmemory_sqlite_pool().await.unwrap();
+
+        let data = MetricsData {
+            keypresses: 10,
+            mouse_clicks: 3,
+            scroll_steps: 7,
+            mouse_distance_in: 42.0,
+        };
+        let scroll = ScrollDirections {
+            up: 4,
+            down: 3,
+            left: 0,
+            right: 0,
+        };
+
+        persist_metrics_sqlite(&pool, &data, &scroll).await.unwrap();
+
+        let (keys, clicks, scrolls, distance) = load_initial_totals(&pool).await.unwrap();
+        assert_eq!(keys, 10);
+        assert_eq!(clicks, 3);
+        assert_eq!(scrolls, 7);
+        assert!((distance - 42.0).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn persist_metrics_sqlite_does_not_skip_purely_horizontal_scroll() {
+        let pool = setup_inmemory_sqlite_pool().await.unwrap();
+
+        // delta_x-only scroll: legacy `scroll_steps` (vertical-only) stays 0,
+        // so the skip-check must also look at `scroll.right` or this row is
+        // silently dropped instead of persisted.
+        let data = MetricsData {
+            keypresses: 0,
+            mouse_clicks: 0,
+            scroll_steps: 0,
+            mouse_distance_in: 0.0,
+        };
+        let scroll = ScrollDirections {
+            up: 0,
+            down: 0,
+            left: 0,
+            right: 5,
+        };
+
+        persist_metrics_sqlite(&pool, &data, &scroll).await.unwrap();
+
+        let row_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM metrics")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(row_count, 1);
+    }
+
+    #[tokio::test]
+    async fn enqueue_outbox_round_trips_through_fetch_and_mark_synced() {
+        let pool = setup_inmemory_sqlite_pool().await.unwrap();
+
+        let data = MetricsData {
+            keypresses: 1,
+            mouse_clicks: 0,
+            scroll_steps: 0,
+            mouse_distance_in: 0.0,
+        };
+        let scroll = ScrollDirections::default();
+
+        enqueue_outbox(&pool, &data, &scroll).await.unwrap();
+
+        let pending = fetch_pending_outbox(&pool, 10, 10).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].data.keypresses, 1);
+
+        let ids: Vec<i64> = pending.iter().map(|row| row.id).collect();
+        mark_outbox_synced(&pool, &ids).await.unwrap();
+
+        let pending_after = fetch_pending_outbox(&pool, 10, 10).await.unwrap();
+        assert!(pending_after.is_empty());
+    }
+
+    #[tokio::test]
+    async fn fetch_pending_outbox_excludes_rows_past_max_retries() {
+        let pool = setup_inmemory_sqlite_pool().await.unwrap();
+
+        let data = MetricsData {
+            keypresses: 1,
+            mouse_clicks: 0,
+            scroll_steps: 0,
+            mouse_distance_in: 0.0,
+        };
+        enqueue_outbox(&pool, &data, &ScrollDirections::default())
+            .await
+            .unwrap();
+
+        let pending = fetch_pending_outbox(&pool, 10, 3).await.unwrap();
+        let ids: Vec<i64> = pending.iter().map(|row| row.id).collect();
+
+        for _ in 0..3 {
+            increment_outbox_retry(&pool, &ids).await.unwrap();
+        }
+
+        let pending_after = fetch_pending_outbox(&pool, 10, 3).await.unwrap();
+        assert!(pending_after.is_empty(), "row should be quarantined once retry_count reaches max_retries");
+    }
+
+    #[tokio::test]
+    async fn fetch_pending_outbox_marks_bucket_repairs_as_not_live() {
+        let pool = setup_inmemory_sqlite_pool().await.unwrap();
+
+        let data = MetricsData {
+            keypresses: 1,
+            mouse_clicks: 0,
+            scroll_steps: 0,
+            mouse_distance_in: 0.0,
+        };
+        enqueue_outbox(&pool, &data, &ScrollDirections::default())
+            .await
+            .unwrap();
+        enqueue_outbox_for_bucket(&pool, &data, &ScrollDirections::default(), "2026-07-29")
+            .await
+            .unwrap();
+
+        let pending = fetch_pending_outbox(&pool, 10, 10).await.unwrap();
+        let live: Vec<bool> = pending.iter().map(|row| row.is_live).collect();
+        assert_eq!(live, vec![true, false], "a fresh interval row should be live, a scrub repair should not be");
+    }
+
+    #[tokio::test]
+    async fn scrub_position_round_trips() {
+        let pool = setup_inmemory_sqlite_pool().await.unwrap();
+
+        assert_eq!(get_scrub_position(&pool).await.unwrap(), "");
+
+        set_scrub_position(&pool, "2026-07-29").await.unwrap();
+        assert_eq!(get_scrub_position(&pool).await.unwrap(), "2026-07-29");
+    }
 }
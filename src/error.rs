@@ -14,8 +14,25 @@ pub enum AppError {
     Platform(#[from] crate::platform::PlatformError),
     #[error("Initialization error: {0}")]
     Initialization(String),
+    #[error("Worker panicked: {0}")]
+    WorkerPanic(String),
+    #[error("Worker failed: {0}")]
+    WorkerFailed(String),
     #[error(transparent)]
     Anyhow(#[from] anyhow::Error),
 }
 
 pub type Result<T> = std::result::Result<T, AppError>;
+
+impl AppError {
+    /// Returns the inner `sqlx::Error` if this error wraps one, so callers
+    /// that only know how to classify a raw `sqlx::Error` (e.g.
+    /// `db::is_transient_error`) can do so without matching on `AppError`'s
+    /// own variants.
+    pub fn as_sqlx(&self) -> Option<&sqlx::Error> {
+        match self {
+            AppError::Database(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use crate::config::RemoteDatabaseSettings;
+use crate::db;
+use crate::error::Result;
+use crate::worker::{Worker, WorkerState};
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time;
+use tracing::{debug, info, warn};
+
+/// Shared, swappable handle to the remote Postgres pool. `pg_pool_option`
+/// used to be decided once at startup and handed out by value, so a
+/// Postgres outage permanently demoted the app to SQLite-only even after
+/// the database recovered. This handle lets the health monitor replace a
+/// dead/absent pool with a freshly reconnected one in place, and every
+/// consumer that holds a clone of the handle picks up the change on its
+/// next read.
+#[derive(Clone, Default)]
+pub struct RemotePoolHandle {
+    pool: Arc<RwLock<Option<Pool<Postgres>>>>,
+    healthy: Arc<AtomicBool>,
+}
+
+impl RemotePoolHandle {
+    pub fn new(pool: Option<Pool<Postgres>>) -> Self {
+        let healthy = pool.is_some();
+        Self {
+            pool: Arc::new(RwLock::new(pool)),
+            healthy: Arc::new(AtomicBool::new(healthy)),
+        }
+    }
+
+    /// Returns the currently active pool, if remote sync is configured and
+    /// has connected successfully at least once.
+    pub async fn get(&self) -> Option<Pool<Postgres>> {
+        self.pool.read().await.clone()
+    }
+
+    /// Whether the most recent health check against the remote pool
+    /// succeeded. `main`'s SIGUSR1 handler reads this to log remote-sync
+    /// health alongside `BackgroundManager::log_status`.
+    pub fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::Relaxed)
+    }
+
+    async fn replace(&self, pool: Pool<Postgres>) {
+        *self.pool.write().await = Some(pool);
+        self.healthy.store(true, Ordering::Relaxed);
+    }
+
+    fn mark_unhealthy(&self) {
+        self.healthy.store(false, Ordering::Relaxed);
+    }
+}
+
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Periodically checks the remote pool's health with a cheap `SELECT 1`.
+/// When the pool is absent or unhealthy it attempts to (re)connect with the
+/// same backoff used at startup; once a fresh pool is healthy it swaps it
+/// into `handle` and re-runs migrations, so the persist path transparently
+/// resumes writing to the recovered remote. Exits (as `WorkerState::Done`)
+/// if no remote Postgres URL is configured, since there's nothing to watch.
+pub struct HealthMonitorWorker {
+    handle: RemotePoolHandle,
+    url: Option<String>,
+    remote_settings: RemoteDatabaseSettings,
+    interval_timer: time::Interval,
+}
+
+impl HealthMonitorWorker {
+    pub fn new(handle: RemotePoolHandle, remote_settings: RemoteDatabaseSettings) -> Self {
+        let url = remote_settings
+            .postgres_url
+            .clone()
+            .filter(|u| !u.is_empty());
+        Self {
+            handle,
+            url,
+            remote_settings,
+            interval_timer: time::interval(HEALTH_CHECK_INTERVAL),
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for HealthMonitorWorker {
+    fn name(&self) -> &str {
+        "health_monitor"
+    }
+
+    async fn step(&mut self) -> Result<WorkerState> {
+        let Some(url) = self.url.clone() else {
+            debug!("No remote Postgres URL configured; health monitor exiting.");
+            return Ok(WorkerState::Done);
+        };
+
+        self.interval_timer.tick().await;
+
+        let current = self.handle.get().await;
+        match current {
+            Some(pool) => match sqlx::query("SELECT 1").execute(&pool).await {
+                Ok(_) => {
+                    self.handle.healthy.store(true, Ordering::Relaxed);
+                }
+                Err(e) => {
+                    warn!("Remote Postgres health check failed: {}. Reconnecting...", e);
+                    self.handle.mark_unhealthy();
+                    reconnect(
+                        &self.handle,
+                        &url,
+                        self.remote_settings.connect_max_elapsed(),
+                    )
+                    .await;
+                }
+            },
+            None => {
+                debug!("Remote Postgres pool absent, attempting to (re)connect...");
+                reconnect(
+                    &self.handle,
+                    &url,
+                    self.remote_settings.connect_max_elapsed(),
+                )
+                .await;
+            }
+        }
+
+        Ok(WorkerState::Active)
+    }
+}
+
+async fn reconnect(handle: &RemotePoolHandle, url: &str, max_elapsed: Duration) {
+    if let Some(pool) = db::connect_postgres_with_backoff(url, max_elapsed).await {
+        db::run_postgres_migrations(&pool).await;
+        handle.replace(pool).await;
+        info!("Remote Postgres pool recovered and marked healthy.");
+    }
+}
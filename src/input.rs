@@ -1,19 +1,36 @@
-use crate::error::Result;
+use crate::error::{AppError, Result};
+use crate::worker::{Worker, WorkerState};
+use async_trait::async_trait;
 use rdev::{listen, Event, EventType};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     mpsc, Arc,
 };
 use std::thread;
+use std::time::Duration;
 use tokio::sync::mpsc::Sender;
 use tracing::{error, info};
 
+/// How often [`InputWorker`] polls the rdev thread's liveness flag.
+const ALIVE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
 #[derive(Debug, Clone, Copy)]
 pub enum InputEvent {
-    KeyPress,
-    MouseClick,
+    KeyPress(rdev::Key),
+    MouseClick(rdev::Button),
     MouseMove(i32, i32),
-    Scroll(i32),
+    Scroll { delta_x: i32, delta_y: i32 },
+}
+
+/// Stable string label for a key, used as the `key_counts` table key.
+pub fn key_label(key: rdev::Key) -> String {
+    format!("{:?}", key)
+}
+
+/// Stable string label for a mouse button, used as the `button_counts` table
+/// key.
+pub fn button_label(button: rdev::Button) -> String {
+    format!("{:?}", button)
 }
 
 /// Structure to control the input listener thread
@@ -21,6 +38,7 @@ pub struct InputListener {
     running: Arc<AtomicBool>,
     thread_handle: Option<thread::JoinHandle<()>>,
     stop_sender: mpsc::Sender<()>,
+    alive: Arc<AtomicBool>,
 }
 
 impl InputListener {
@@ -43,29 +61,27 @@ impl InputListener {
             }
         }
     }
+
+    /// Returns a cloneable handle that reports whether the listener thread
+    /// is still running, so it can be supervised (via [`InputWorker`])
+    /// without handing over the `JoinHandle` itself — `main` still owns
+    /// that for the final `stop`/`join` at shutdown.
+    pub fn alive_handle(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.alive)
+    }
 }
 
 /// Convert rdev events to our internal event representation
 fn convert_event(event: &Event) -> Option<InputEvent> {
     match event.event_type {
-        EventType::KeyPress(_) => Some(InputEvent::KeyPress),
-        EventType::ButtonPress(button) => {
-            if button == rdev::Button::Left || button == rdev::Button::Right {
-                Some(InputEvent::MouseClick)
-            } else {
-                None
-            }
-        }
+        EventType::KeyPress(key) => Some(InputEvent::KeyPress(key)),
+        EventType::ButtonPress(button) => Some(InputEvent::MouseClick(button)),
         EventType::MouseMove { x, y } => Some(InputEvent::MouseMove(x as i32, y as i32)),
-        EventType::Wheel { delta_y, .. } => {
-            // Convert to absolute value for scroll steps
-            let scroll_amount = if delta_y != 0 {
-                delta_y.abs() as i32
-            } else {
-                0
-            };
-            if scroll_amount > 0 {
-                Some(InputEvent::Scroll(scroll_amount))
+        EventType::Wheel { delta_x, delta_y } => {
+            let delta_x = delta_x as i32;
+            let delta_y = delta_y as i32;
+            if delta_x != 0 || delta_y != 0 {
+                Some(InputEvent::Scroll { delta_x, delta_y })
             } else {
                 None
             }
@@ -82,6 +98,9 @@ pub async fn listen_for_input(tx: Sender<InputEvent>) -> Result<InputListener> {
     let running = Arc::new(AtomicBool::new(true));
     let running_clone = Arc::clone(&running);
 
+    let alive = Arc::new(AtomicBool::new(true));
+    let alive_clone = Arc::clone(&alive);
+
     // Create a channel to signal the thread to stop
     let (stop_sender, stop_receiver) = mpsc::channel::<()>();
 
@@ -128,15 +147,62 @@ pub async fn listen_for_input(tx: Sender<InputEvent>) -> Result<InputListener> {
         }
 
         info!("Input listener thread exiting");
+        alive_clone.store(false, Ordering::SeqCst);
     });
 
     let listener = InputListener {
         running,
         thread_handle: Some(thread_handle),
         stop_sender,
+        alive,
     };
 
     info!("Input listener task spawned");
     Ok(listener)
 }
 
+/// Supervises the dedicated rdev thread's liveness so the input
+/// listener participates in the same coordinated-failure machinery as
+/// processing and persistence: an unexpected exit (the thread panicking or
+/// `rdev::listen` erroring out) is reported as a failed `step`, which
+/// `BackgroundManager` retries with backoff and, after
+/// `MAX_CONSECUTIVE_FAILURES`, escalates into a daemon-wide shutdown. The
+/// `JoinHandle` itself stays with `main`, which still owns the final
+/// `stop`/`join` at shutdown; this worker only watches the liveness flag.
+pub struct InputWorker {
+    alive: Arc<AtomicBool>,
+    dead: bool,
+}
+
+impl InputWorker {
+    pub fn new(alive: Arc<AtomicBool>) -> Self {
+        Self { alive, dead: false }
+    }
+}
+
+#[async_trait]
+impl Worker for InputWorker {
+    fn name(&self) -> &str {
+        "input_listener"
+    }
+
+    async fn step(&mut self) -> Result<WorkerState> {
+        if self.dead {
+            return Err(AppError::WorkerFailed(
+                "input listener thread has exited; no input is being captured".to_string(),
+            ));
+        }
+
+        tokio::time::sleep(ALIVE_POLL_INTERVAL).await;
+
+        if self.alive.load(Ordering::SeqCst) {
+            Ok(WorkerState::Idle)
+        } else {
+            self.dead = true;
+            Err(AppError::WorkerFailed(
+                "input listener thread exited unexpectedly".to_string(),
+            ))
+        }
+    }
+}
+
@@ -0,0 +1,144 @@
+use crate::db::MetricsData;
+use crate::error::Result;
+use crate::health::RemotePoolHandle;
+use crate::worker::{Worker, WorkerState};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// Postgres NOTIFY/LISTEN channel carrying live metrics deltas across
+/// devices sharing the same remote database.
+pub const NOTIFY_CHANNEL: &str = "etsu_metrics";
+
+const RECONNECT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const RECONNECT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+static DEVICE_ID: Lazy<String> = Lazy::new(|| Uuid::new_v4().to_string());
+
+/// A stable identifier for this process' device, used to attribute live
+/// metrics deltas when aggregating across machines.
+pub fn device_id() -> &'static str {
+    &DEVICE_ID
+}
+
+/// The interval delta broadcast over `NOTIFY_CHANNEL` after each successful
+/// remote persist of a live interval; scrub repairs and outbox backlog
+/// replayed after an outage are persisted without a notify.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsDelta {
+    pub device_id: String,
+    pub keypresses: usize,
+    pub mouse_clicks: usize,
+    pub scroll_steps: usize,
+    pub mouse_distance_in: f64,
+}
+
+impl MetricsDelta {
+    pub fn from_data(data: &MetricsData, device_id: impl Into<String>) -> Self {
+        Self {
+            device_id: device_id.into(),
+            keypresses: data.keypresses,
+            mouse_clicks: data.mouse_clicks,
+            scroll_steps: data.scroll_steps,
+            mouse_distance_in: data.mouse_distance_in,
+        }
+    }
+}
+
+/// Subscribes to `NOTIFY_CHANNEL` on a dedicated Postgres connection and
+/// republishes each delta on `tx` for the rest of the app (e.g. a live
+/// dashboard) to consume. Reconnects with backoff and re-LISTENs whenever
+/// the underlying connection drops, and re-resolves the pool through
+/// `remote_pool` so it keeps working once the health monitor recovers a
+/// previously absent/dead pool.
+pub struct LiveSubscriberWorker {
+    remote_pool: RemotePoolHandle,
+    tx: broadcast::Sender<MetricsDelta>,
+    listener: Option<PgListener>,
+    backoff: Duration,
+}
+
+impl LiveSubscriberWorker {
+    pub fn new(remote_pool: RemotePoolHandle, tx: broadcast::Sender<MetricsDelta>) -> Self {
+        Self {
+            remote_pool,
+            tx,
+            listener: None,
+            backoff: RECONNECT_INITIAL_BACKOFF,
+        }
+    }
+
+    async fn back_off(&mut self) {
+        debug!("Reconnecting live metrics listener in {:?}...", self.backoff);
+        tokio::time::sleep(self.backoff).await;
+        self.backoff = (self.backoff * 2).min(RECONNECT_MAX_BACKOFF);
+    }
+}
+
+#[async_trait]
+impl Worker for LiveSubscriberWorker {
+    fn name(&self) -> &str {
+        "live_subscriber"
+    }
+
+    async fn step(&mut self) -> Result<WorkerState> {
+        if self.listener.is_none() {
+            let Some(pg_pool) = self.remote_pool.get().await else {
+                tokio::time::sleep(self.backoff).await;
+                return Ok(WorkerState::Idle);
+            };
+
+            match PgListener::connect_with(&pg_pool).await {
+                Ok(mut listener) => match listener.listen(NOTIFY_CHANNEL).await {
+                    Ok(()) => {
+                        self.backoff = RECONNECT_INITIAL_BACKOFF;
+                        info!("Subscribed to live metrics channel '{}'.", NOTIFY_CHANNEL);
+                        self.listener = Some(listener);
+                        return Ok(WorkerState::Active);
+                    }
+                    Err(e) => {
+                        warn!("Failed to LISTEN on '{}': {}", NOTIFY_CHANNEL, e);
+                        self.back_off().await;
+                        return Ok(WorkerState::Idle);
+                    }
+                },
+                Err(e) => {
+                    warn!("Failed to establish live metrics listener connection: {}", e);
+                    self.back_off().await;
+                    return Ok(WorkerState::Idle);
+                }
+            }
+        }
+
+        let listener = self
+            .listener
+            .as_mut()
+            .expect("listener populated by the branch above");
+        match listener.recv().await {
+            Ok(notification) => {
+                match serde_json::from_str::<MetricsDelta>(notification.payload()) {
+                    Ok(delta) => {
+                        debug!(
+                            "Received live metrics delta from device {}",
+                            delta.device_id
+                        );
+                        let _ = self.tx.send(delta);
+                    }
+                    Err(e) => warn!("Failed to parse live metrics payload: {}", e),
+                }
+                Ok(WorkerState::Active)
+            }
+            Err(e) => {
+                warn!("Live metrics listener connection lost: {}", e);
+                self.listener = None;
+                self.back_off().await;
+                Ok(WorkerState::Idle)
+            }
+        }
+    }
+}
@@ -4,30 +4,52 @@ mod config;
 mod db;
 mod distance;
 mod error;
+mod health;
 mod input;
+mod live;
+mod monitor_watch;
+mod outbox;
 mod persistence;
 mod platform;
 mod processing;
+mod query;
+mod scrub;
 mod state;
+mod worker;
 
 use crate::error::Result;
 use directories::ProjectDirs;
 use error::AppError;
+use health::HealthMonitorWorker;
+use live::LiveSubscriberWorker;
+use monitor_watch::MonitorWatchWorker;
+use outbox::OutboxSyncWorker;
+use persistence::PersistenceWorker;
+use processing::ProcessingWorker;
+use scrub::{ScrubCommand, ScrubWorker};
 use state::MetricsState;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::thread;
 use tokio::sync::{broadcast, mpsc};
-use tracing::{debug, error, info, warn};
+use tracing::{error, info, warn};
 use tracing_appender::rolling;
 use tracing_subscriber::EnvFilter;
+use worker::BackgroundManager;
 
 use futures::stream::StreamExt;
 use signal_hook::consts::signal::*;
 use signal_hook_tokio::Signals;
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    info!("Etsu starting...");
-
+/// Real process entry point. Stays synchronous (no `#[tokio::main]`) because
+/// GLFW's Cocoa backend on macOS requires init and event-polling to happen
+/// on the true OS main thread, not merely a single consistent thread —
+/// spawning GLFW onto its own worker thread (as the async side does for
+/// every other background task) would misbehave or abort there. The rest of
+/// the daemon, including the tokio runtime, instead runs on a dedicated
+/// thread spawned from here, leaving this thread free to do nothing but
+/// pump GLFW's event loop until that thread signals shutdown.
+fn main() -> Result<()> {
     let settings = config::Settings::load().map_err(|e| {
         eprintln!("FATAL: Failed to load configuration: {}", e);
         e
@@ -39,20 +61,93 @@ async fn main() -> Result<()> {
     std::fs::create_dir_all(log_dir)
         .map_err(|e| AppError::Initialization(format!("Failed to create log directory: {}", e)))?;
     let _log_file = rolling::daily(log_dir, "etsu.log");
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            EnvFilter::try_new(&settings.log_level).unwrap_or_else(|_| EnvFilter::new("info")),
-        )
-        .with_writer(_log_file)
-        .with_ansi(false) // Disable colors in file
-        .init();
+    if settings.log_format_is_json() {
+        tracing_subscriber::fmt()
+            .with_env_filter(
+                EnvFilter::try_new(&settings.log_level).unwrap_or_else(|_| EnvFilter::new("info")),
+            )
+            .with_writer(_log_file)
+            .with_ansi(false) // Disable colors in file
+            .json()
+            .init();
+    } else {
+        tracing_subscriber::fmt()
+            .with_env_filter(
+                EnvFilter::try_new(&settings.log_level).unwrap_or_else(|_| EnvFilter::new("info")),
+            )
+            .with_writer(_log_file)
+            .with_ansi(false) // Disable colors in file
+            .init();
+    }
 
+    info!("Etsu starting...");
     info!("Loaded configuration");
 
-    if let Err(e) = platform::initialize_monitor_info() {
-        error!("Failed to initialize monitor info using GLFW: {}. Distance calculation might be inaccurate or use defaults.", e);
+    let (monitor_tx, monitor_rx) = mpsc::channel::<Vec<platform::MonitorInfo>>(4);
+
+    match monitor_watch::init() {
+        Ok((glfw, monitor_changed)) => {
+            // Set by `run` as soon as its own shutdown sequence starts, not
+            // only once it returns: the async side waits for this loop to
+            // drop `monitor_tx` before its monitor-watch worker is allowed
+            // to finish, so flipping this late would make every shutdown
+            // wait out that worker's timeout instead of exiting promptly.
+            let glfw_stop = Arc::new(AtomicBool::new(false));
+            let glfw_stop_clone = Arc::clone(&glfw_stop);
+
+            let async_thread = thread::Builder::new()
+                .name("etsu-async".into())
+                .spawn(move || {
+                    let result = build_tokio_runtime()
+                        .block_on(run(settings, monitor_rx, Arc::clone(&glfw_stop_clone)));
+                    // Safety net for an early return (e.g. a startup error)
+                    // that never reached the shutdown sequence below.
+                    glfw_stop_clone.store(true, Ordering::SeqCst);
+                    result
+                })
+                .map_err(|e| {
+                    AppError::Initialization(format!(
+                        "Failed to spawn async runtime thread: {}",
+                        e
+                    ))
+                })?;
+
+            monitor_watch::run_event_loop(glfw, monitor_changed, monitor_tx, glfw_stop);
+
+            join_async_thread(async_thread)
+        }
+        Err(e) => {
+            error!(
+                "Failed to initialize GLFW for monitor detection: {}. Distance calculation might be inaccurate or use defaults.",
+                e
+            );
+            drop(monitor_tx);
+            build_tokio_runtime().block_on(run(settings, monitor_rx, Arc::new(AtomicBool::new(false))))
+        }
+    }
+}
+
+fn build_tokio_runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Runtime::new().expect("Failed to build the Tokio runtime")
+}
+
+fn join_async_thread(handle: thread::JoinHandle<Result<()>>) -> Result<()> {
+    match handle.join() {
+        Ok(result) => result,
+        Err(_) => Err(AppError::Initialization(
+            "Async runtime thread panicked".into(),
+        )),
     }
+}
 
+/// Everything that isn't GLFW: database setup, background workers, signal
+/// handling, and graceful shutdown. Runs on its own tokio runtime on a
+/// dedicated thread so `main`'s real OS thread stays free for GLFW.
+async fn run(
+    settings: config::Settings,
+    monitor_rx: mpsc::Receiver<Vec<platform::MonitorInfo>>,
+    glfw_stop: Arc<AtomicBool>,
+) -> Result<()> {
     let local_db_path = settings
         .get_local_sqlite_path()?
         .to_string_lossy()
@@ -60,63 +155,105 @@ async fn main() -> Result<()> {
     let (sqlite_pool, pg_pool_option) =
         db::setup_database_pools(&local_db_path, &settings.database).await?;
 
-    if let Err(e) = db::run_migrations(&sqlite_pool, &pg_pool_option).await {
-        error!(
-            "Database migration failed: {}. Application might not function correctly.",
-            e
-        );
-        // Consider exiting if migrations are critical
-        // return Err(e);
-    }
+    let sqlite_pool = match db::run_migrations(sqlite_pool, &local_db_path, &pg_pool_option).await
+    {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Database migration failed: {}. Exiting.", e);
+            return Err(e);
+        }
+    };
+
+    let remote_pool = health::RemotePoolHandle::new(pg_pool_option.clone());
 
     let (shutdown_tx, _) = broadcast::channel::<()>(1);
     let shutdown_tx_clone = shutdown_tx.clone();
 
+    let background_manager = BackgroundManager::new();
+
+    let sqlite_pool_clone = sqlite_pool.clone();
+    let remote_pool_clone = remote_pool.clone();
+    let (scrub_worker, scrub_control_tx) = ScrubWorker::new(
+        sqlite_pool_clone,
+        remote_pool_clone,
+        settings.scrub_tranquility(),
+    );
+
     let signals = setup_signal_handlers(shutdown_tx_clone)?;
 
-    let signal_task = tokio::spawn(handle_signals(signals, shutdown_tx.clone()));
+    let signal_task = tokio::spawn(handle_signals(
+        signals,
+        shutdown_tx.clone(),
+        background_manager.clone(),
+        remote_pool.clone(),
+        scrub_control_tx,
+    ));
 
     let metrics_state = Arc::new(MetricsState::default());
     let (input_tx, input_rx) = mpsc::channel::<input::InputEvent>(1024);
 
     info!("Spawning core tasks...");
 
-    input::listen_for_input(input_tx).await?;
+    let mut input_listener = input::listen_for_input(input_tx).await?;
+    let input_worker = input::InputWorker::new(input_listener.alive_handle());
+    let input_handle = background_manager
+        .spawn(input_worker, shutdown_tx.clone())
+        .await;
+
+    let monitor_watch_worker = MonitorWatchWorker::new(monitor_rx);
+    let monitor_watch_task = background_manager
+        .spawn(monitor_watch_worker, shutdown_tx.clone())
+        .await;
 
     let metrics_state_clone = Arc::clone(&metrics_state);
     let processing_interval = settings.processing_interval();
 
-    let mut shutdown_rx1 = shutdown_tx.subscribe();
-    let processing_handle = tokio::spawn(async move {
-        tokio::select! {
-            res = processing::aggregate_metrics(input_rx, metrics_state_clone, processing_interval) => res,
-            _ = shutdown_rx1.recv() => {
-                debug!("Processing task received shutdown signal");
-                Ok(())
-            }
-        }
-    });
+    let processing_worker =
+        ProcessingWorker::new(input_rx, metrics_state_clone, processing_interval);
+    let processing_handle = background_manager
+        .spawn(processing_worker, shutdown_tx.clone())
+        .await;
 
     let metrics_state_clone = Arc::clone(&metrics_state);
     let saving_interval = settings.saving_interval();
     let sqlite_pool_clone = sqlite_pool.clone();
-    let pg_pool_option_clone = pg_pool_option.clone();
-
-    let mut shutdown_rx2 = shutdown_tx.subscribe();
-    let persistence_handle = tokio::spawn(async move {
-        tokio::select! {
-            res = persistence::save_metrics_periodically(
-                metrics_state_clone,
-                sqlite_pool_clone,
-                pg_pool_option_clone,
-                saving_interval,
-            ) => res,
-            _ = shutdown_rx2.recv() => {
-                debug!("Persistence task received shutdown signal");
-                Ok(())
-            }
-        }
-    });
+
+    let persistence_worker = PersistenceWorker::new(
+        metrics_state_clone,
+        sqlite_pool_clone,
+        saving_interval,
+        settings.log_format_is_json(),
+    )
+    .await;
+    let persistence_handle = background_manager
+        .spawn(persistence_worker, shutdown_tx.clone())
+        .await;
+
+    let scrub_handle = background_manager
+        .spawn(scrub_worker, shutdown_tx.clone())
+        .await;
+
+    let outbox_interval = settings.outbox_interval();
+    let sqlite_pool_clone = sqlite_pool.clone();
+    let remote_pool_clone = remote_pool.clone();
+    let outbox_worker = OutboxSyncWorker::new(sqlite_pool_clone, remote_pool_clone, outbox_interval);
+    let outbox_handle = background_manager
+        .spawn(outbox_worker, shutdown_tx.clone())
+        .await;
+
+    let (live_metrics_tx, _live_metrics_rx) = broadcast::channel::<live::MetricsDelta>(256);
+    let remote_pool_clone = remote_pool.clone();
+    let live_subscriber_worker = LiveSubscriberWorker::new(remote_pool_clone, live_metrics_tx);
+    let live_subscriber_handle = background_manager
+        .spawn(live_subscriber_worker, shutdown_tx.clone())
+        .await;
+
+    let remote_pool_clone = remote_pool.clone();
+    let remote_settings = settings.database.clone();
+    let health_worker = HealthMonitorWorker::new(remote_pool_clone, remote_settings);
+    let health_handle = background_manager
+        .spawn(health_worker, shutdown_tx.clone())
+        .await;
 
     info!("All tasks spawned. Etsu running in background.");
     info!("Press Ctrl+C to exit");
@@ -130,14 +267,45 @@ async fn main() -> Result<()> {
     info!("Shutting down tasks...");
 
     info!("Stopping input listener...");
+    input_listener.stop();
+
+    info!("Stopping monitor watch event loop...");
+    glfw_stop.store(true, Ordering::SeqCst);
 
     let timeout = tokio::time::Duration::from_secs(5);
 
+    let input_timeout = tokio::time::timeout(timeout, input_handle);
     let processing_timeout = tokio::time::timeout(timeout, processing_handle);
     let persistence_timeout = tokio::time::timeout(timeout, persistence_handle);
-
-    let (processing_result, persistence_result) =
-        tokio::join!(processing_timeout, persistence_timeout);
+    let scrub_timeout = tokio::time::timeout(timeout, scrub_handle);
+    let monitor_watch_timeout = tokio::time::timeout(timeout, monitor_watch_task);
+    let outbox_timeout = tokio::time::timeout(timeout, outbox_handle);
+    let live_subscriber_timeout = tokio::time::timeout(timeout, live_subscriber_handle);
+    let health_timeout = tokio::time::timeout(timeout, health_handle);
+
+    let (
+        input_result,
+        processing_result,
+        persistence_result,
+        scrub_result,
+        monitor_watch_result,
+        outbox_result,
+        live_subscriber_result,
+        health_result,
+    ) = tokio::join!(
+        input_timeout,
+        processing_timeout,
+        persistence_timeout,
+        scrub_timeout,
+        monitor_watch_timeout,
+        outbox_timeout,
+        live_subscriber_timeout,
+        health_timeout
+    );
+
+    if input_result.is_err() {
+        warn!("Input listener supervisor did not complete within timeout, aborting");
+    }
 
     if processing_result.is_err() {
         warn!("Processing task did not complete within timeout, aborting");
@@ -147,10 +315,32 @@ async fn main() -> Result<()> {
         warn!("Persistence task did not complete within timeout, aborting");
     }
 
+    if scrub_result.is_err() {
+        warn!("Scrub task did not complete within timeout, aborting");
+    }
+
+    if monitor_watch_result.is_err() {
+        warn!("Monitor watch task did not complete within timeout, aborting");
+    }
+
+    if outbox_result.is_err() {
+        warn!("Outbox sync task did not complete within timeout, aborting");
+    }
+
+    if live_subscriber_result.is_err() {
+        warn!("Live metrics subscriber task did not complete within timeout, aborting");
+    }
+
+    if health_result.is_err() {
+        warn!("Remote pool health monitor did not complete within timeout, aborting");
+    }
+
+    let _ = tokio::task::spawn_blocking(move || input_listener.join()).await;
+
     info!("Closing database pools...");
     let close_sqlite = tokio::spawn(async move { sqlite_pool.close().await });
     let close_pg = tokio::spawn(async move {
-        if let Some(pg_pool) = pg_pool_option {
+        if let Some(pg_pool) = remote_pool.get().await {
             pg_pool.close().await;
         }
     });
@@ -161,11 +351,12 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-/// Sets up the signal handlers for SIGTERM, SIGINT, and SIGQUIT
+/// Sets up the signal handlers for SIGTERM, SIGINT, SIGQUIT, SIGUSR1, and
+/// SIGUSR2
 fn setup_signal_handlers(_shutdown_tx: broadcast::Sender<()>) -> Result<Signals> {
     info!("Setting up signal handlers...");
 
-    let signals = match Signals::new([SIGTERM, SIGINT, SIGQUIT]) {
+    let signals = match Signals::new([SIGTERM, SIGINT, SIGQUIT, SIGUSR1, SIGUSR2]) {
         Ok(s) => s,
         Err(e) => {
             error!("Failed to register signal handlers: {}", e);
@@ -180,8 +371,18 @@ fn setup_signal_handlers(_shutdown_tx: broadcast::Sender<()>) -> Result<Signals>
     Ok(signals)
 }
 
-/// Handles signals and triggers shutdown
-async fn handle_signals(mut signals: Signals, shutdown_tx: broadcast::Sender<()>) {
+/// Handles signals: SIGTERM/SIGINT/SIGQUIT trigger shutdown, SIGUSR1 dumps
+/// the current background worker status and remote-sync health to the
+/// tracing log, and SIGUSR2 toggles pausing/resuming the scrub worker's
+/// reconciliation passes.
+async fn handle_signals(
+    mut signals: Signals,
+    shutdown_tx: broadcast::Sender<()>,
+    background_manager: BackgroundManager,
+    remote_pool: health::RemotePoolHandle,
+    scrub_control_tx: mpsc::Sender<ScrubCommand>,
+) {
+    let mut scrub_paused = false;
     while let Some(signal) = signals.next().await {
         match signal {
             SIGTERM | SIGINT | SIGQUIT => {
@@ -189,6 +390,29 @@ async fn handle_signals(mut signals: Signals, shutdown_tx: broadcast::Sender<()>
                 let _ = shutdown_tx.send(());
                 break;
             }
+            SIGUSR1 => {
+                info!("Received SIGUSR1, dumping background worker status...");
+                background_manager.log_status().await;
+                info!(
+                    "Remote Postgres sync health: {}",
+                    if remote_pool.is_healthy() { "healthy" } else { "unhealthy" }
+                );
+            }
+            SIGUSR2 => {
+                scrub_paused = !scrub_paused;
+                let command = if scrub_paused {
+                    ScrubCommand::Pause
+                } else {
+                    ScrubCommand::Resume
+                };
+                info!(
+                    "Received SIGUSR2, {} scrub worker...",
+                    if scrub_paused { "pausing" } else { "resuming" }
+                );
+                if scrub_control_tx.send(command).await.is_err() {
+                    warn!("Scrub worker control channel closed; ignoring SIGUSR2.");
+                }
+            }
             _ => warn!("Received unexpected signal: {}", signal),
         }
     }
@@ -0,0 +1,108 @@
+use crate::error::Result;
+use crate::platform::{self, MonitorInfo, PlatformError};
+use crate::worker::{Worker, WorkerState};
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::info;
+
+/// How often the main-thread event loop polls for pending GLFW events. GLFW
+/// only dispatches its monitor-connect callback while `poll_events` is
+/// called, so this is effectively the watcher's reaction latency to a
+/// hotplug.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Initializes GLFW, detects and caches the current monitor layout, and
+/// registers the monitor-change callback backing [`run_event_loop`]. GLFW is
+/// not thread-safe and on macOS its Cocoa backend requires init and
+/// event-polling to happen on the process's real main thread, not merely a
+/// single consistent thread — so unlike the rest of this daemon's
+/// background work, the returned `glfw::Glfw` can't be handed to a spawned
+/// thread. Callers must run the async side of the daemon on its own thread
+/// and drive [`run_event_loop`] here instead.
+pub fn init() -> Result<(glfw::Glfw, Arc<AtomicBool>)> {
+    info!("Initializing GLFW for monitor detection...");
+
+    let mut glfw = glfw::init(glfw::fail_on_errors).map_err(PlatformError::GlfwInit)?;
+
+    let monitors = platform::detect_monitors(&mut glfw);
+    platform::cache_monitors(monitors)?;
+
+    let changed = Arc::new(AtomicBool::new(false));
+    let changed_clone = Arc::clone(&changed);
+    glfw.set_monitor_callback(move |_monitor, _event| {
+        changed_clone.store(true, Ordering::SeqCst);
+    });
+
+    Ok((glfw, changed))
+}
+
+/// Drives GLFW's event loop on the calling thread until `stop` is set to
+/// `true`, re-detecting the full monitor layout (position, resolution, PPI)
+/// and sending it to `MonitorWatchWorker` over `tx` whenever the
+/// monitor-change callback registered by [`init`] fires. Blocks the calling
+/// thread for as long as the daemon runs, so this must be called from the
+/// process's real main thread after the rest of the daemon has been handed
+/// off to its own tokio runtime on a separate thread.
+pub fn run_event_loop(
+    mut glfw: glfw::Glfw,
+    changed: Arc<AtomicBool>,
+    tx: mpsc::Sender<Vec<MonitorInfo>>,
+    stop: Arc<AtomicBool>,
+) {
+    info!("Monitor watch event loop started on the main thread.");
+
+    while !stop.load(Ordering::SeqCst) {
+        glfw.poll_events();
+
+        if changed.swap(false, Ordering::SeqCst) {
+            let monitors = platform::detect_monitors(&mut glfw);
+            info!(
+                "Monitor configuration changed, re-detected {} monitor(s).",
+                monitors.len()
+            );
+            if tx.try_send(monitors).is_err() {
+                // Receiver gone (shutting down) or backed up; either way,
+                // the next change will retry.
+            }
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    info!("Monitor watch event loop exiting.");
+}
+
+/// Drains monitor lists re-detected by the main-thread GLFW event loop and
+/// swaps them into `platform`'s cache, so `get_monitor_for_point` (and
+/// therefore distance calculation) picks up hotplug/resolution changes
+/// without a restart.
+pub struct MonitorWatchWorker {
+    rx: mpsc::Receiver<Vec<MonitorInfo>>,
+}
+
+impl MonitorWatchWorker {
+    pub fn new(rx: mpsc::Receiver<Vec<MonitorInfo>>) -> Self {
+        Self { rx }
+    }
+}
+
+#[async_trait]
+impl Worker for MonitorWatchWorker {
+    fn name(&self) -> &str {
+        "monitor_watch"
+    }
+
+    async fn step(&mut self) -> Result<WorkerState> {
+        match self.rx.recv().await {
+            Some(monitors) => {
+                platform::cache_monitors(monitors)?;
+                Ok(WorkerState::Active)
+            }
+            None => Ok(WorkerState::Done),
+        }
+    }
+}
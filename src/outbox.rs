@@ -0,0 +1,292 @@
+use crate::db;
+use crate::error::Result;
+use crate::health::RemotePoolHandle;
+use crate::worker::{Worker, WorkerState};
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres, Sqlite};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time;
+use tracing::{debug, error, instrument, warn};
+
+const BATCH_SIZE: u64 = 100;
+const IDLE_BACKOFF: Duration = Duration::from_secs(5);
+const TRANSIENT_BACKOFF: Duration = Duration::from_secs(10);
+/// A row that has failed this many replay attempts is quarantined: excluded
+/// from future batches so it can't block every row behind it forever, but
+/// left unsynced in its staging table for an operator to inspect.
+const MAX_OUTBOX_RETRIES: i64 = 10;
+
+/// Drains the local `pg_outbox`, `key_count_outbox`, and `button_count_outbox`
+/// staging tables and replays each to Postgres in timestamp (insertion)
+/// order. Rows are marked synced only once the remote write is confirmed, so
+/// a restart simply replays whatever is still pending — including on first
+/// startup after an extended outage. Reads the remote pool through
+/// `remote_pool` on every step so it keeps working once the health monitor
+/// reconnects a previously absent/dead pool.
+pub struct OutboxSyncWorker {
+    sqlite_pool: Pool<Sqlite>,
+    remote_pool: RemotePoolHandle,
+    poll_interval: Duration,
+}
+
+impl OutboxSyncWorker {
+    pub fn new(
+        sqlite_pool: Pool<Sqlite>,
+        remote_pool: RemotePoolHandle,
+        poll_interval: Duration,
+    ) -> Self {
+        Self {
+            sqlite_pool,
+            remote_pool,
+            poll_interval,
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for OutboxSyncWorker {
+    fn name(&self) -> &str {
+        "outbox_sync"
+    }
+
+    #[instrument(skip(self))]
+    async fn step(&mut self) -> Result<WorkerState> {
+        let Some(pg_pool) = self.remote_pool.get().await else {
+            time::sleep(self.poll_interval).await;
+            return Ok(WorkerState::Idle);
+        };
+
+        let mut replayed_any = false;
+        let mut backoff = None;
+
+        match drain_batch(&self.sqlite_pool, &pg_pool).await {
+            Ok(0) => {}
+            Ok(replayed) => {
+                replayed_any = true;
+                debug!(
+                    "Outbox sync replayed {} metrics row(s) to Postgres.",
+                    replayed
+                );
+            }
+            Err(e) if e.as_sqlx().is_some_and(db::is_transient_error) => {
+                warn!(
+                    "Transient error draining metrics outbox, backing off {:?}: {}",
+                    TRANSIENT_BACKOFF, e
+                );
+                backoff.get_or_insert(TRANSIENT_BACKOFF);
+            }
+            Err(e) => {
+                error!("Metrics outbox batch failed: {}", e);
+                backoff.get_or_insert(IDLE_BACKOFF);
+            }
+        }
+
+        match drain_key_count_batch(&self.sqlite_pool, &pg_pool).await {
+            Ok(0) => {}
+            Ok(replayed) => {
+                replayed_any = true;
+                debug!(
+                    "Outbox sync replayed {} key count row(s) to Postgres.",
+                    replayed
+                );
+            }
+            Err(e) if e.as_sqlx().is_some_and(db::is_transient_error) => {
+                warn!(
+                    "Transient error draining key count outbox, backing off {:?}: {}",
+                    TRANSIENT_BACKOFF, e
+                );
+                backoff.get_or_insert(TRANSIENT_BACKOFF);
+            }
+            Err(e) => {
+                error!("Key count outbox batch failed: {}", e);
+                backoff.get_or_insert(IDLE_BACKOFF);
+            }
+        }
+
+        match drain_button_count_batch(&self.sqlite_pool, &pg_pool).await {
+            Ok(0) => {}
+            Ok(replayed) => {
+                replayed_any = true;
+                debug!(
+                    "Outbox sync replayed {} button count row(s) to Postgres.",
+                    replayed
+                );
+            }
+            Err(e) if e.as_sqlx().is_some_and(db::is_transient_error) => {
+                warn!(
+                    "Transient error draining button count outbox, backing off {:?}: {}",
+                    TRANSIENT_BACKOFF, e
+                );
+                backoff.get_or_insert(TRANSIENT_BACKOFF);
+            }
+            Err(e) => {
+                error!("Button count outbox batch failed: {}", e);
+                backoff.get_or_insert(IDLE_BACKOFF);
+            }
+        }
+
+        match backoff {
+            Some(d) => {
+                time::sleep(d).await;
+                Ok(WorkerState::Idle)
+            }
+            None if !replayed_any => {
+                time::sleep(self.poll_interval).await;
+                Ok(WorkerState::Idle)
+            }
+            None => Ok(WorkerState::Active),
+        }
+    }
+}
+
+/// Drains one batch of pending `pg_outbox` rows into a single Postgres
+/// transaction, so the remote either gains the whole batch or none of it.
+/// A row's `bucket_date` (set by `scrub::ScrubWorker` for drift repairs)
+/// rides along so the replay lands under that day's timestamp rather than
+/// Postgres's `DEFAULT now()`, and its `is_live` flag rides along so only
+/// rows that synced promptly trigger the live `pg_notify`, not scrub repairs
+/// or backlog that piled up during an outage. Returns the number of rows
+/// successfully replayed.
+async fn drain_batch(sqlite_pool: &Pool<Sqlite>, pg_pool: &Pool<Postgres>) -> Result<usize> {
+    let pending = db::fetch_pending_outbox(sqlite_pool, BATCH_SIZE, MAX_OUTBOX_RETRIES).await?;
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let ids: Vec<i64> = pending.iter().map(|row| row.id).collect();
+    let rows: Vec<_> = pending
+        .iter()
+        .map(|row| (row.data.clone(), row.scroll, row.bucket_date.clone(), row.is_live))
+        .collect();
+
+    match db::persist_metrics_batch_postgres(pg_pool, &rows).await {
+        Ok(()) => {
+            db::mark_outbox_synced(sqlite_pool, &ids).await?;
+            Ok(ids.len())
+        }
+        Err(e) => {
+            db::increment_outbox_retry(sqlite_pool, &ids).await?;
+            warn_near_quarantine("pg_outbox", pending.iter().map(|row| (row.id, row.retry_count)));
+            Err(e)
+        }
+    }
+}
+
+/// Drains one batch of pending `key_count_outbox` rows, summing same-key
+/// deltas before replaying them to Postgres in a single transaction.
+/// Returns the number of rows successfully replayed.
+async fn drain_key_count_batch(sqlite_pool: &Pool<Sqlite>, pg_pool: &Pool<Postgres>) -> Result<usize> {
+    let pending =
+        db::fetch_pending_key_count_outbox(sqlite_pool, BATCH_SIZE, MAX_OUTBOX_RETRIES).await?;
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let ids: Vec<i64> = pending.iter().map(|row| row.id).collect();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for row in &pending {
+        *counts.entry(row.name.clone()).or_insert(0) += row.delta as usize;
+    }
+
+    match db::persist_key_counts_postgres(pg_pool, &counts).await {
+        Ok(()) => {
+            db::mark_key_count_outbox_synced(sqlite_pool, &ids).await?;
+            Ok(ids.len())
+        }
+        Err(e) => {
+            db::increment_key_count_outbox_retry(sqlite_pool, &ids).await?;
+            warn_near_quarantine(
+                "key_count_outbox",
+                pending.iter().map(|row| (row.id, row.retry_count)),
+            );
+            Err(e)
+        }
+    }
+}
+
+/// Mouse-button counterpart of [`drain_key_count_batch`] for `button_count_outbox`.
+async fn drain_button_count_batch(
+    sqlite_pool: &Pool<Sqlite>,
+    pg_pool: &Pool<Postgres>,
+) -> Result<usize> {
+    let pending =
+        db::fetch_pending_button_count_outbox(sqlite_pool, BATCH_SIZE, MAX_OUTBOX_RETRIES).await?;
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let ids: Vec<i64> = pending.iter().map(|row| row.id).collect();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for row in &pending {
+        *counts.entry(row.name.clone()).or_insert(0) += row.delta as usize;
+    }
+
+    match db::persist_button_counts_postgres(pg_pool, &counts).await {
+        Ok(()) => {
+            db::mark_button_count_outbox_synced(sqlite_pool, &ids).await?;
+            Ok(ids.len())
+        }
+        Err(e) => {
+            db::increment_button_count_outbox_retry(sqlite_pool, &ids).await?;
+            warn_near_quarantine(
+                "button_count_outbox",
+                pending.iter().map(|row| (row.id, row.retry_count)),
+            );
+            Err(e)
+        }
+    }
+}
+
+/// Logs a warning for any row about to be quarantined (its retry count will
+/// reach [`MAX_OUTBOX_RETRIES`] once `increment_*_outbox_retry` commits), so
+/// an operator can investigate before it's silently excluded from future
+/// batches.
+fn warn_near_quarantine(table: &str, rows: impl Iterator<Item = (i64, i64)>) {
+    for (id, retry_count) in rows {
+        if retry_count + 1 >= MAX_OUTBOX_RETRIES {
+            warn!(
+                "{} row {} has failed {} replay attempt(s) and will be quarantined.",
+                table,
+                id,
+                retry_count + 1
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{MetricsData, ScrollDirections};
+
+    #[tokio::test]
+    async fn step_leaves_rows_pending_without_a_remote_pool() {
+        let sqlite_pool = db::setup_inmemory_sqlite_pool().await.unwrap();
+        let data = MetricsData {
+            keypresses: 1,
+            mouse_clicks: 0,
+            scroll_steps: 0,
+            mouse_distance_in: 0.0,
+        };
+        db::enqueue_outbox(&sqlite_pool, &data, &ScrollDirections::default())
+            .await
+            .unwrap();
+
+        let remote_pool = RemotePoolHandle::new(None);
+        let mut worker =
+            OutboxSyncWorker::new(sqlite_pool.clone(), remote_pool, Duration::from_millis(1));
+
+        let result = worker.step().await.unwrap();
+        assert_eq!(result, WorkerState::Idle);
+
+        let pending = db::fetch_pending_outbox(&sqlite_pool, BATCH_SIZE, MAX_OUTBOX_RETRIES)
+            .await
+            .unwrap();
+        assert_eq!(
+            pending.len(),
+            1,
+            "row should remain pending when there's no remote pool to drain into"
+        );
+    }
+}
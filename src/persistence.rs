@@ -1,73 +1,248 @@
-use crate::db::{self, MetricsData};
-use crate::error::Result;
+use crate::db::{self, MetricsData, ScrollDirections};
+use crate::error::{AppError, Result};
 use crate::state::MetricsState;
-use sqlx::{Pool, Postgres, Sqlite};
+use crate::worker::{Worker, WorkerState};
+use async_trait::async_trait;
+use sqlx::{Pool, Sqlite};
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time;
-use tracing::{debug, error, instrument};
+use tracing::{debug, error, info};
 
-#[instrument(skip(state, sqlite_pool, pg_pool_option, saving_interval))]
-pub async fn save_metrics_periodically(
+/// On each tick of `saving_interval`, drains the accumulated interval
+/// metrics out of `MetricsState`, persists them locally, and enqueues them
+/// in the local outbox tables for durable replay to the remote Postgres
+/// database by `outbox::OutboxSyncWorker`. `step` returns the first local
+/// SQLite failure it hits (after still attempting every other write for
+/// this tick), so a persistently broken local database is supervised and
+/// restarted like any other worker failure rather than just logged forever.
+pub struct PersistenceWorker {
     state: Arc<MetricsState>,
     sqlite_pool: Pool<Sqlite>,
-    pg_pool_option: Option<Pool<Postgres>>,
-    saving_interval: Duration,
-) -> Result<()> {
-    debug!(
-        "Starting metrics persistence task with interval: {:?}",
-        saving_interval
-    );
-    let mut interval_timer = time::interval(saving_interval);
-
-    match db::load_initial_totals(&sqlite_pool).await {
-        Ok((keys, clicks, scrolls, distance)) => {
-            state.total.keypresses.store(keys, Ordering::Relaxed);
-            state.total.mouse_clicks.store(clicks, Ordering::Relaxed);
-            state.total.scroll_steps.store(scrolls, Ordering::Relaxed);
-            *state.total.mouse_distance_in.lock().await = distance;
-            debug!("Successfully loaded initial totals into state from local DB.");
+    interval_timer: time::Interval,
+    log_structured_events: bool,
+}
+
+impl PersistenceWorker {
+    pub async fn new(
+        state: Arc<MetricsState>,
+        sqlite_pool: Pool<Sqlite>,
+        saving_interval: Duration,
+        log_structured_events: bool,
+    ) -> Self {
+        match db::load_initial_totals(&sqlite_pool).await {
+            Ok((keys, clicks, scrolls, distance)) => {
+                state.total.keypresses.store(keys, Ordering::Relaxed);
+                state.total.mouse_clicks.store(clicks, Ordering::Relaxed);
+                state.total.scroll_steps.store(scrolls, Ordering::Relaxed);
+                *state.total.mouse_distance_in.lock().await = distance;
+                debug!("Successfully loaded initial totals into state from local DB.");
+            }
+            Err(e) => {
+                error!(
+                    "Failed to load initial totals from local DB: {}. Starting from zero.",
+                    e
+                );
+            }
         }
-        Err(e) => {
-            error!(
-                "Failed to load initial totals from local DB: {}. Starting from zero.",
-                e
-            );
+
+        Self {
+            state,
+            sqlite_pool,
+            interval_timer: time::interval(saving_interval),
+            log_structured_events,
         }
     }
+}
+
+#[async_trait]
+impl Worker for PersistenceWorker {
+    fn name(&self) -> &str {
+        "persistence"
+    }
 
-    loop {
-        interval_timer.tick().await;
+    async fn step(&mut self) -> Result<WorkerState> {
+        self.interval_timer.tick().await;
 
-        let (keys, clicks, scrolls, distance) = state.interval.reset().await;
+        let snapshot = self.state.interval.reset().await;
+        self.state.total.add_interval(&snapshot).await;
 
-        state
-            .total
-            .add_interval(keys, clicks, scrolls, distance)
-            .await;
+        let keys = snapshot.keypresses;
+        let clicks = snapshot.mouse_clicks;
+        let scrolls = snapshot.scroll_steps;
+        let distance = snapshot.mouse_distance_in;
+        let mut did_work = false;
+        // Local SQLite failures here are not transient network blips like the
+        // ones `outbox::OutboxSyncWorker` backs off on — a disk-full or
+        // corrupted local DB won't clear up on retry. Every write below still
+        // gets attempted so one failing table doesn't mask another, but the
+        // first failure is surfaced so `BackgroundManager::spawn`'s
+        // supervised restart/fail-fast actually engages instead of this
+        // worker logging forever at a false-healthy status.
+        let mut first_err: Option<AppError> = None;
 
-        if keys > 0 || clicks > 0 || scrolls > 0 || distance > 0.0 {
+        if keys > 0
+            || clicks > 0
+            || scrolls > 0
+            || distance > 0.0
+            || snapshot.scroll_up > 0
+            || snapshot.scroll_down > 0
+            || snapshot.scroll_left > 0
+            || snapshot.scroll_right > 0
+        {
+            did_work = true;
             let data_to_save = MetricsData {
                 keypresses: keys,
                 mouse_clicks: clicks,
                 scroll_steps: scrolls,
                 mouse_distance_in: distance,
             };
+            let scroll_to_save = ScrollDirections {
+                up: snapshot.scroll_up,
+                down: snapshot.scroll_down,
+                left: snapshot.scroll_left,
+                right: snapshot.scroll_right,
+            };
             debug!(
                 "Attempting to persist metrics: K={}, C={}, S={}, D={:.2}",
                 keys, clicks, scrolls, distance
             );
 
-            if let Err(e) = db::persist_metrics_sqlite(&sqlite_pool, &data_to_save).await {
+            if let Err(e) =
+                db::persist_metrics_sqlite(&self.sqlite_pool, &data_to_save, &scroll_to_save).await
+            {
                 error!("Failed to persist metrics to local SQLite: {}", e);
+                first_err.get_or_insert(e);
+            }
+
+            let remote_enqueued =
+                match db::enqueue_outbox(&self.sqlite_pool, &data_to_save, &scroll_to_save).await {
+                    Ok(()) => true,
+                    Err(e) => {
+                        error!("Failed to enqueue metrics in local outbox: {}", e);
+                        first_err.get_or_insert(e);
+                        false
+                    }
+                };
+
+            if self.log_structured_events {
+                info!(
+                    interval.keypresses = keys,
+                    interval.mouse_clicks = clicks,
+                    interval.scroll_steps = scrolls,
+                    interval.mouse_distance_in = distance,
+                    interval.remote_enqueued = remote_enqueued,
+                    "persistence interval"
+                );
+            }
+        }
+
+        if !snapshot.key_counts.is_empty() {
+            did_work = true;
+            if let Err(e) =
+                db::persist_key_counts_sqlite(&self.sqlite_pool, &snapshot.key_counts).await
+            {
+                error!("Failed to persist key counts to local SQLite: {}", e);
+                first_err.get_or_insert(e);
+            }
+
+            if let Err(e) =
+                db::enqueue_key_count_outbox(&self.sqlite_pool, &snapshot.key_counts).await
+            {
+                error!("Failed to enqueue key counts in local outbox: {}", e);
+                first_err.get_or_insert(e);
+            }
+        }
+
+        if !snapshot.button_counts.is_empty() {
+            did_work = true;
+            if let Err(e) =
+                db::persist_button_counts_sqlite(&self.sqlite_pool, &snapshot.button_counts).await
+            {
+                error!("Failed to persist button counts to local SQLite: {}", e);
+                first_err.get_or_insert(e);
             }
 
-            if let Some(ref pg_pool) = pg_pool_option {
-                if let Err(e) = db::persist_metrics_postgres(pg_pool, &data_to_save).await {
-                    error!("Failed to persist metrics to remote Postgres: {}", e);
-                }
+            if let Err(e) =
+                db::enqueue_button_count_outbox(&self.sqlite_pool, &snapshot.button_counts).await
+            {
+                error!("Failed to enqueue button counts in local outbox: {}", e);
+                first_err.get_or_insert(e);
             }
         }
+
+        if let Some(e) = first_err {
+            return Err(e);
+        }
+
+        Ok(if did_work {
+            WorkerState::Active
+        } else {
+            WorkerState::Idle
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::setup_inmemory_sqlite_pool;
+
+    #[tokio::test]
+    async fn step_persists_and_enqueues_accumulated_metrics() {
+        let pool = setup_inmemory_sqlite_pool().await.unwrap();
+        let state = Arc::new(MetricsState::default());
+        state.interval.keypresses.fetch_add(5, Ordering::Relaxed);
+        state.interval.mouse_clicks.fetch_add(2, Ordering::Relaxed);
+
+        let mut worker =
+            PersistenceWorker::new(state, pool.clone(), Duration::from_millis(1), false).await;
+
+        let result = worker.step().await.unwrap();
+        assert_eq!(result, WorkerState::Active);
+
+        let (keys, clicks, _, _) = db::load_initial_totals(&pool).await.unwrap();
+        assert_eq!(keys, 5);
+        assert_eq!(clicks, 2);
+
+        let pending = db::fetch_pending_outbox(&pool, 10, 10).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].data.keypresses, 5);
+        assert_eq!(pending[0].data.mouse_clicks, 2);
+        assert!(pending[0].bucket_date.is_none());
+    }
+
+    #[tokio::test]
+    async fn step_persists_purely_horizontal_scroll() {
+        let pool = setup_inmemory_sqlite_pool().await.unwrap();
+        let state = Arc::new(MetricsState::default());
+        state.interval.scroll_left.fetch_add(3, Ordering::Relaxed);
+
+        let mut worker =
+            PersistenceWorker::new(state, pool.clone(), Duration::from_millis(1), false).await;
+
+        let result = worker.step().await.unwrap();
+        assert_eq!(result, WorkerState::Active);
+
+        let pending = db::fetch_pending_outbox(&pool, 10, 10).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].scroll.left, 3);
+    }
+
+    #[tokio::test]
+    async fn step_is_idle_when_nothing_accumulated() {
+        let pool = setup_inmemory_sqlite_pool().await.unwrap();
+        let state = Arc::new(MetricsState::default());
+
+        let mut worker =
+            PersistenceWorker::new(state, pool.clone(), Duration::from_millis(1), false).await;
+
+        let result = worker.step().await.unwrap();
+        assert_eq!(result, WorkerState::Idle);
+        assert!(db::fetch_pending_outbox(&pool, 10, 10)
+            .await
+            .unwrap()
+            .is_empty());
     }
 }
@@ -33,13 +33,10 @@ pub enum PlatformError {
     MonitorNotFound,
 }
 
-/// Initializes GLFW, fetches monitor information, calculates PPI, caches it, and terminates GLFW.
-/// Must be called once at startup from the main thread.
-pub fn initialize_monitor_info() -> std::result::Result<(), PlatformError> {
-    info!("Initializing GLFW for monitor detection...");
-
-    let mut glfw = glfw::init(glfw::fail_on_errors).map_err(PlatformError::GlfwInit)?;
-
+/// Enumerates the currently connected monitors via `glfw` and builds their
+/// `MonitorInfo` (position, resolution, PPI). Shared by `monitor_watch`'s
+/// initial scan and its re-detection on every hotplug/config change.
+pub(crate) fn detect_monitors(glfw: &mut glfw::Glfw) -> Vec<MonitorInfo> {
     let monitors = glfw.with_connected_monitors(|_glfw, monitors| {
         let mut info_list = Vec::new();
         for (index, monitor) in monitors.iter().enumerate() {
@@ -58,12 +55,17 @@ pub fn initialize_monitor_info() -> std::result::Result<(), PlatformError> {
         );
     }
 
+    monitors
+}
+
+/// Atomically replaces the cached monitor list. Readers (`get_cached_monitor_info`,
+/// `get_monitor_for_point`) take the same lock, so a refresh from the monitor
+/// watch thread can never be observed half-written by the processing task.
+pub(crate) fn cache_monitors(monitors: Vec<MonitorInfo>) -> Result<()> {
     let mut cache = MONITOR_INFO_CACHE
         .lock()
-        .map_err(|_| PlatformError::CacheLock)?;
+        .map_err(|_| AppError::Platform(PlatformError::CacheLock))?;
     *cache = Some(monitors);
-
-    info!("Monitor information cached successfully. GLFW terminated.");
     Ok(())
 }
 
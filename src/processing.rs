@@ -1,61 +1,94 @@
 use crate::distance;
 use crate::error::Result;
-use crate::input::InputEvent;
+use crate::input::{self, InputEvent};
 use crate::state::MetricsState;
+use crate::worker::{Worker, WorkerState};
+use async_trait::async_trait;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc::Receiver;
 use tokio::time;
-use tracing::{debug, instrument, warn};
+use tracing::warn;
 
-#[instrument(skip(rx, state, processing_interval))]
-pub async fn aggregate_metrics(
-    mut rx: Receiver<InputEvent>,
+/// Consumes raw input events and folds them into `MetricsState`, and on each
+/// tick of `processing_interval` recomputes mouse travel distance from the
+/// latest cursor position.
+pub struct ProcessingWorker {
+    rx: Receiver<InputEvent>,
     state: Arc<MetricsState>,
-    processing_interval: Duration,
-) -> Result<()> {
-    debug!(
-        "Starting metrics processing task with interval: {:?}",
-        processing_interval
-    );
-    let mut interval_timer = time::interval(processing_interval);
-    let initial_x = state.latest_mouse_x.load(Ordering::Relaxed);
-    let initial_y = state.latest_mouse_y.load(Ordering::Relaxed);
-    state.last_calc_mouse_x.store(initial_x, Ordering::Relaxed);
-    state.last_calc_mouse_y.store(initial_y, Ordering::Relaxed);
+    interval_timer: time::Interval,
+}
+
+impl ProcessingWorker {
+    pub fn new(rx: Receiver<InputEvent>, state: Arc<MetricsState>, processing_interval: Duration) -> Self {
+        let initial_x = state.latest_mouse_x.load(Ordering::Relaxed);
+        let initial_y = state.latest_mouse_y.load(Ordering::Relaxed);
+        state.last_calc_mouse_x.store(initial_x, Ordering::Relaxed);
+        state.last_calc_mouse_y.store(initial_y, Ordering::Relaxed);
+
+        Self {
+            rx,
+            state,
+            interval_timer: time::interval(processing_interval),
+        }
+    }
+}
 
-    loop {
+#[async_trait]
+impl Worker for ProcessingWorker {
+    fn name(&self) -> &str {
+        "processing"
+    }
+
+    async fn step(&mut self) -> Result<WorkerState> {
         tokio::select! {
             biased;
-            Some(event) = rx.recv() => {
-                 match event {
-                    InputEvent::KeyPress => {
-                        state.interval.keypresses.fetch_add(1, Ordering::Relaxed);
+            Some(event) = self.rx.recv() => {
+                match event {
+                    InputEvent::KeyPress(key) => {
+                        self.state.interval.keypresses.fetch_add(1, Ordering::Relaxed);
+                        let mut counts = self.state.interval.key_counts.lock().await;
+                        *counts.entry(input::key_label(key)).or_insert(0) += 1;
                     }
-                    InputEvent::MouseClick => {
-                        state.interval.mouse_clicks.fetch_add(1, Ordering::Relaxed);
+                    InputEvent::MouseClick(button) => {
+                        self.state.interval.mouse_clicks.fetch_add(1, Ordering::Relaxed);
+                        let mut counts = self.state.interval.button_counts.lock().await;
+                        *counts.entry(input::button_label(button)).or_insert(0) += 1;
                     }
-                    InputEvent::Scroll(delta) => {
-                        state.interval.scroll_steps.fetch_add(delta as usize, Ordering::Relaxed);
+                    InputEvent::Scroll { delta_x, delta_y } => {
+                        if delta_y != 0 {
+                            self.state.interval.scroll_steps.fetch_add(delta_y.unsigned_abs() as usize, Ordering::Relaxed);
+                        }
+                        if delta_y > 0 {
+                            self.state.interval.scroll_up.fetch_add(delta_y as usize, Ordering::Relaxed);
+                        } else if delta_y < 0 {
+                            self.state.interval.scroll_down.fetch_add(delta_y.unsigned_abs() as usize, Ordering::Relaxed);
+                        }
+                        if delta_x > 0 {
+                            self.state.interval.scroll_right.fetch_add(delta_x as usize, Ordering::Relaxed);
+                        } else if delta_x < 0 {
+                            self.state.interval.scroll_left.fetch_add(delta_x.unsigned_abs() as usize, Ordering::Relaxed);
+                        }
                     }
                     InputEvent::MouseMove(x, y) => {
-                        state.latest_mouse_x.store(x, Ordering::Relaxed);
-                        state.latest_mouse_y.store(y, Ordering::Relaxed);
+                        self.state.latest_mouse_x.store(x, Ordering::Relaxed);
+                        self.state.latest_mouse_y.store(y, Ordering::Relaxed);
                     }
                 }
+                Ok(WorkerState::Active)
             }
-            _ = interval_timer.tick() => {
-                let current_x = state.latest_mouse_x.load(Ordering::Relaxed);
-                let current_y = state.latest_mouse_y.load(Ordering::Relaxed);
-                let last_x = state.last_calc_mouse_x.load(Ordering::Relaxed);
-                let last_y = state.last_calc_mouse_y.load(Ordering::Relaxed);
+            _ = self.interval_timer.tick() => {
+                let current_x = self.state.latest_mouse_x.load(Ordering::Relaxed);
+                let current_y = self.state.latest_mouse_y.load(Ordering::Relaxed);
+                let last_x = self.state.last_calc_mouse_x.load(Ordering::Relaxed);
+                let last_y = self.state.last_calc_mouse_y.load(Ordering::Relaxed);
 
                 if current_x != last_x || current_y != last_y {
                     match distance::calculate_distance_inches(last_x, last_y, current_x, current_y) {
                         Ok(distance_moved) => {
                             if distance_moved > 0.0 {
-                                let mut dist_lock = state.interval.mouse_distance_in.lock().await;
+                                let mut dist_lock = self.state.interval.mouse_distance_in.lock().await;
                                 *dist_lock += distance_moved;
                             }
                         }
@@ -63,15 +96,14 @@ pub async fn aggregate_metrics(
                             warn!("Failed to calculate mouse distance: {}", e);
                         }
                     }
-                    state.last_calc_mouse_x.store(current_x, Ordering::Relaxed);
-                    state.last_calc_mouse_y.store(current_y, Ordering::Relaxed);
+                    self.state.last_calc_mouse_x.store(current_x, Ordering::Relaxed);
+                    self.state.last_calc_mouse_y.store(current_y, Ordering::Relaxed);
+                    Ok(WorkerState::Active)
+                } else {
+                    Ok(WorkerState::Idle)
                 }
             }
-            else => {
-                debug!("Input channel closed. Exiting processing task.");
-                break;
-            }
+            else => Ok(WorkerState::Done),
         }
     }
-    Ok(())
 }
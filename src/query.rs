@@ -0,0 +1,205 @@
+use crate::db::{MetricsData, MetricsIden, ScrollDirections};
+use crate::error::Result;
+use sea_query::{Alias, Expr, Order, PostgresQueryBuilder, Query, SqliteQueryBuilder};
+use sea_query_binder::SqlxBinder;
+use sqlx::{Pool, Postgres, Row, Sqlite};
+use tracing::instrument;
+
+/// Aggregated totals for a single day bucket (`YYYY-MM-DD`), as returned by
+/// [`aggregate_by_day_sqlite`] / [`aggregate_by_day_postgres`]. Carries the
+/// scroll-direction breakdown alongside `data` so a scrub repair can replay
+/// a bucket's real direction split instead of defaulting to zeros.
+#[derive(Debug, Clone)]
+pub struct BucketTotals {
+    pub bucket: String,
+    pub data: MetricsData,
+    pub scroll: ScrollDirections,
+}
+
+/// Aggregates local SQLite `metrics` rows into per-day totals for the given
+/// `YYYY-MM-DD` day buckets. Built with `sea_query::Query::select()` against
+/// a bound parameter list (`Expr::expr(bucket).is_in(days)`) rather than
+/// string-interpolating the values.
+#[instrument(skip(pool))]
+pub async fn aggregate_by_day_sqlite(pool: &Pool<Sqlite>, days: &[String]) -> Result<Vec<BucketTotals>> {
+    if days.is_empty() {
+        // An empty filter must yield no rows, not all rows.
+        return Ok(Vec::new());
+    }
+
+    let bucket = Expr::cust_with_expr("strftime('%Y-%m-%d', ?)", Expr::col(MetricsIden::Timestamp));
+    let query = Query::select()
+        .expr_as(bucket.clone(), Alias::new("bucket"))
+        .expr_as(Expr::col(MetricsIden::Keypresses).sum(), Alias::new("total_keys"))
+        .expr_as(Expr::col(MetricsIden::MouseClicks).sum(), Alias::new("total_clicks"))
+        .expr_as(Expr::col(MetricsIden::ScrollSteps).sum(), Alias::new("total_scrolls"))
+        .expr_as(
+            Expr::col(MetricsIden::MouseDistanceIn).sum(),
+            Alias::new("total_distance"),
+        )
+        .expr_as(Expr::col(MetricsIden::ScrollUp).sum(), Alias::new("total_scroll_up"))
+        .expr_as(Expr::col(MetricsIden::ScrollDown).sum(), Alias::new("total_scroll_down"))
+        .expr_as(Expr::col(MetricsIden::ScrollLeft).sum(), Alias::new("total_scroll_left"))
+        .expr_as(Expr::col(MetricsIden::ScrollRight).sum(), Alias::new("total_scroll_right"))
+        .from(MetricsIden::Table)
+        .and_where(Expr::expr(bucket.clone()).is_in(days.iter().cloned()))
+        .add_group_by([bucket.clone()])
+        .order_by_expr(bucket, Order::Asc)
+        .to_owned();
+
+    let (sql, values) = query.build_sqlx(SqliteQueryBuilder);
+    let rows = sqlx::query_with(&sql, values).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            let keys: i64 = r.try_get("total_keys").unwrap_or(0);
+            let clicks: i64 = r.try_get("total_clicks").unwrap_or(0);
+            let scrolls: i64 = r.try_get("total_scrolls").unwrap_or(0);
+            let distance: f64 = r.try_get("total_distance").unwrap_or(0.0);
+            let scroll_up: i64 = r.try_get("total_scroll_up").unwrap_or(0);
+            let scroll_down: i64 = r.try_get("total_scroll_down").unwrap_or(0);
+            let scroll_left: i64 = r.try_get("total_scroll_left").unwrap_or(0);
+            let scroll_right: i64 = r.try_get("total_scroll_right").unwrap_or(0);
+            BucketTotals {
+                bucket: r.try_get("bucket").unwrap_or_default(),
+                data: MetricsData {
+                    keypresses: keys as usize,
+                    mouse_clicks: clicks as usize,
+                    scroll_steps: scrolls as usize,
+                    mouse_distance_in: distance,
+                },
+                scroll: ScrollDirections {
+                    up: scroll_up as usize,
+                    down: scroll_down as usize,
+                    left: scroll_left as usize,
+                    right: scroll_right as usize,
+                },
+            }
+        })
+        .collect())
+}
+
+/// Returns up to `limit` distinct day buckets (`YYYY-MM-DD`) in the local
+/// `metrics` table that are strictly after `since` and strictly before
+/// today, oldest first. Today is always excluded because its bucket is
+/// still being written to and would look perpetually "behind" on Postgres.
+#[instrument(skip(pool))]
+pub async fn pending_day_buckets_sqlite(
+    pool: &Pool<Sqlite>,
+    since: &str,
+    limit: i64,
+) -> Result<Vec<String>> {
+    let bucket = Expr::cust_with_expr("strftime('%Y-%m-%d', ?)", Expr::col(MetricsIden::Timestamp));
+    let today = Expr::cust("strftime('%Y-%m-%d', 'now')");
+    let query = Query::select()
+        .distinct()
+        .expr_as(bucket.clone(), Alias::new("bucket"))
+        .from(MetricsIden::Table)
+        .and_where(Expr::expr(bucket.clone()).gt(since))
+        .and_where(Expr::expr(bucket).lt(today))
+        .order_by(Alias::new("bucket"), Order::Asc)
+        .limit(limit as u64)
+        .to_owned();
+
+    let (sql, values) = query.build_sqlx(SqliteQueryBuilder);
+    let rows = sqlx::query_with(&sql, values).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| r.try_get("bucket").unwrap_or_default())
+        .collect())
+}
+
+/// Aggregates remote Postgres `metrics` rows into per-day totals for the
+/// given `YYYY-MM-DD` day buckets. Filters with `= ANY($1)` against a single
+/// bound array of `days` rather than an `IN (...)` list, so the query text
+/// (and therefore the prepared-statement shape in Postgres's plan cache)
+/// doesn't vary with the number of buckets requested.
+#[instrument(skip(pool))]
+pub async fn aggregate_by_day_postgres(
+    pool: &Pool<Postgres>,
+    days: &[String],
+) -> Result<Vec<BucketTotals>> {
+    if days.is_empty() {
+        // `= ANY` over an empty array correctly yields no rows, but we
+        // short-circuit explicitly rather than relying on that subtlety.
+        return Ok(Vec::new());
+    }
+
+    let bucket = Expr::cust_with_expr("to_char($1, 'YYYY-MM-DD')", Expr::col(MetricsIden::Timestamp));
+    let query = Query::select()
+        .expr_as(bucket.clone(), Alias::new("bucket"))
+        .expr_as(
+            Expr::cust_with_expr("($1)::BIGINT", Expr::col(MetricsIden::Keypresses).sum()),
+            Alias::new("total_keys"),
+        )
+        .expr_as(
+            Expr::cust_with_expr("($1)::BIGINT", Expr::col(MetricsIden::MouseClicks).sum()),
+            Alias::new("total_clicks"),
+        )
+        .expr_as(
+            Expr::cust_with_expr("($1)::BIGINT", Expr::col(MetricsIden::ScrollSteps).sum()),
+            Alias::new("total_scrolls"),
+        )
+        .expr_as(
+            Expr::cust_with_expr(
+                "($1)::DOUBLE PRECISION",
+                Expr::col(MetricsIden::MouseDistanceIn).sum(),
+            ),
+            Alias::new("total_distance"),
+        )
+        .expr_as(
+            Expr::cust_with_expr("($1)::BIGINT", Expr::col(MetricsIden::ScrollUp).sum()),
+            Alias::new("total_scroll_up"),
+        )
+        .expr_as(
+            Expr::cust_with_expr("($1)::BIGINT", Expr::col(MetricsIden::ScrollDown).sum()),
+            Alias::new("total_scroll_down"),
+        )
+        .expr_as(
+            Expr::cust_with_expr("($1)::BIGINT", Expr::col(MetricsIden::ScrollLeft).sum()),
+            Alias::new("total_scroll_left"),
+        )
+        .expr_as(
+            Expr::cust_with_expr("($1)::BIGINT", Expr::col(MetricsIden::ScrollRight).sum()),
+            Alias::new("total_scroll_right"),
+        )
+        .from(MetricsIden::Table)
+        .and_where(Expr::expr(bucket.clone()).eq(Expr::cust_with_values("ANY($1)", [days.to_vec()])))
+        .add_group_by([bucket.clone()])
+        .order_by_expr(bucket, Order::Asc)
+        .to_owned();
+
+    let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+    let rows = sqlx::query_with(&sql, values).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            let keys: i64 = r.try_get("total_keys").unwrap_or(0);
+            let clicks: i64 = r.try_get("total_clicks").unwrap_or(0);
+            let scrolls: i64 = r.try_get("total_scrolls").unwrap_or(0);
+            let distance: f64 = r.try_get("total_distance").unwrap_or(0.0);
+            let scroll_up: i64 = r.try_get("total_scroll_up").unwrap_or(0);
+            let scroll_down: i64 = r.try_get("total_scroll_down").unwrap_or(0);
+            let scroll_left: i64 = r.try_get("total_scroll_left").unwrap_or(0);
+            let scroll_right: i64 = r.try_get("total_scroll_right").unwrap_or(0);
+            BucketTotals {
+                bucket: r.try_get("bucket").unwrap_or_default(),
+                data: MetricsData {
+                    keypresses: keys as usize,
+                    mouse_clicks: clicks as usize,
+                    scroll_steps: scrolls as usize,
+                    mouse_distance_in: distance,
+                },
+                scroll: ScrollDirections {
+                    up: scroll_up as usize,
+                    down: scroll_down as usize,
+                    left: scroll_left as usize,
+                    right: scroll_right as usize,
+                },
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,342 @@
+use crate::db::{self, MetricsData, ScrollDirections};
+use crate::error::Result;
+use crate::health::RemotePoolHandle;
+use crate::query::{self, BucketTotals};
+use crate::worker::{Worker, WorkerState};
+use async_trait::async_trait;
+use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::time;
+use tracing::{debug, info, warn};
+
+/// Number of day buckets compared against Postgres per scrub pass.
+const BUCKET_BATCH_SIZE: i64 = 7;
+/// How long a paused worker waits between checks for a `Resume`/`Cancel`.
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Floor on the rest between passes. Once caught up, `reconcile_next_batch`
+/// finds no pending buckets and returns almost instantly, so scaling purely
+/// off elapsed processing time would turn the worker into a busy-loop
+/// regardless of `tranquility`; this keeps a steady-state idle pass no more
+/// frequent than once per floor.
+const MIN_IDLE_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Control messages for [`ScrubWorker`], sent over the channel returned by
+/// [`ScrubWorker::new`] so reconciliation can be paused or stopped on
+/// demand without tearing down the whole daemon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrubCommand {
+    Pause,
+    Resume,
+    Cancel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScrubMode {
+    Running,
+    Paused,
+}
+
+/// Periodically compares local SQLite and remote Postgres day-bucket totals
+/// and re-enqueues whatever Postgres is missing via
+/// `db::enqueue_outbox_for_bucket`, which stamps the repair with the bucket
+/// it belongs to so it replays under that day in Postgres rather than
+/// today's, so a dropped remote write or a crash between the local and
+/// remote inserts doesn't silently drift the two stores apart forever.
+/// Walks forward through day buckets from the
+/// last persisted position (`db::get_scrub_position`/`set_scrub_position`),
+/// so a restart resumes the scan rather than rescanning history. After each
+/// batch it rests for `tranquility * batch_processing_time` (floored at
+/// `MIN_IDLE_BACKOFF` once caught up, so an empty pass doesn't busy-loop),
+/// so raising `tranquility` linearly reduces the load this worker puts on
+/// both databases.
+pub struct ScrubWorker {
+    sqlite_pool: Pool<Sqlite>,
+    remote_pool: RemotePoolHandle,
+    tranquility: u32,
+    mode: ScrubMode,
+    commands: mpsc::Receiver<ScrubCommand>,
+}
+
+impl ScrubWorker {
+    pub fn new(
+        sqlite_pool: Pool<Sqlite>,
+        remote_pool: RemotePoolHandle,
+        tranquility: u32,
+    ) -> (Self, mpsc::Sender<ScrubCommand>) {
+        let (tx, rx) = mpsc::channel(8);
+        (
+            Self {
+                sqlite_pool,
+                remote_pool,
+                tranquility: tranquility.max(1),
+                mode: ScrubMode::Running,
+                commands: rx,
+            },
+            tx,
+        )
+    }
+
+    /// Compares the next unreconciled batch of day buckets and re-enqueues
+    /// any drift found. Returns whether it found work to do.
+    async fn reconcile_next_batch(&mut self) -> Result<bool> {
+        let Some(pg_pool) = self.remote_pool.get().await else {
+            debug!("Scrub worker skipping pass: remote Postgres pool unavailable.");
+            return Ok(false);
+        };
+
+        let position = db::get_scrub_position(&self.sqlite_pool).await?;
+        let buckets =
+            query::pending_day_buckets_sqlite(&self.sqlite_pool, &position, BUCKET_BATCH_SIZE)
+                .await?;
+        if buckets.is_empty() {
+            return Ok(false);
+        }
+
+        let local_totals = query::aggregate_by_day_sqlite(&self.sqlite_pool, &buckets).await?;
+        let remote_totals = query::aggregate_by_day_postgres(&pg_pool, &buckets).await?;
+        let remote_by_bucket: HashMap<&str, &BucketTotals> = remote_totals
+            .iter()
+            .map(|t| (t.bucket.as_str(), t))
+            .collect();
+
+        let mut repaired = 0usize;
+        for local in &local_totals {
+            let missing = match remote_by_bucket.get(local.bucket.as_str()) {
+                Some(remote) => missing_totals(local, remote),
+                None => Some((local.data.clone(), local.scroll)),
+            };
+
+            if let Some((missing_data, missing_scroll)) = missing {
+                warn!(
+                    "Scrub worker found drift in bucket {}: re-pushing K={}, C={}, S={}, D={:.2} to Postgres.",
+                    local.bucket,
+                    missing_data.keypresses,
+                    missing_data.mouse_clicks,
+                    missing_data.scroll_steps,
+                    missing_data.mouse_distance_in
+                );
+                db::enqueue_outbox_for_bucket(
+                    &self.sqlite_pool,
+                    &missing_data,
+                    &missing_scroll,
+                    &local.bucket,
+                )
+                .await?;
+                repaired += 1;
+            }
+
+            // Persisted once per bucket, right after its repair (if any) is
+            // enqueued, rather than once at the end of the whole batch: a
+            // crash (or a later bucket's `?` error) between an enqueue and
+            // a batch-end position write would otherwise leave the position
+            // stale, so a restart recomputes the same already-repaired
+            // drift against Postgres and enqueues a duplicate repair for it.
+            db::set_scrub_position(&self.sqlite_pool, &local.bucket).await?;
+        }
+
+        info!(
+            "Scrub worker reconciled {} day bucket(s), repaired {}.",
+            buckets.len(),
+            repaired
+        );
+
+        Ok(true)
+    }
+}
+
+/// Returns the amount by which `local` exceeds `remote`, across both the
+/// rolled-up totals and the scroll-direction breakdown, or `None` if
+/// `remote` is already caught up in every field. Comparing the directions
+/// too means a repair carries the bucket's real up/down/left/right split
+/// instead of silently zeroing it out.
+fn missing_totals(local: &BucketTotals, remote: &BucketTotals) -> Option<(MetricsData, ScrollDirections)> {
+    let missing_data = MetricsData {
+        keypresses: local.data.keypresses.saturating_sub(remote.data.keypresses),
+        mouse_clicks: local.data.mouse_clicks.saturating_sub(remote.data.mouse_clicks),
+        scroll_steps: local.data.scroll_steps.saturating_sub(remote.data.scroll_steps),
+        mouse_distance_in: (local.data.mouse_distance_in - remote.data.mouse_distance_in).max(0.0),
+    };
+    let missing_scroll = ScrollDirections {
+        up: local.scroll.up.saturating_sub(remote.scroll.up),
+        down: local.scroll.down.saturating_sub(remote.scroll.down),
+        left: local.scroll.left.saturating_sub(remote.scroll.left),
+        right: local.scroll.right.saturating_sub(remote.scroll.right),
+    };
+
+    let has_drift = missing_data.keypresses > 0
+        || missing_data.mouse_clicks > 0
+        || missing_data.scroll_steps > 0
+        || missing_data.mouse_distance_in > 0.0
+        || missing_scroll.up > 0
+        || missing_scroll.down > 0
+        || missing_scroll.left > 0
+        || missing_scroll.right > 0;
+
+    has_drift.then_some((missing_data, missing_scroll))
+}
+
+#[async_trait]
+impl Worker for ScrubWorker {
+    fn name(&self) -> &str {
+        "scrub"
+    }
+
+    async fn step(&mut self) -> Result<WorkerState> {
+        if self.mode == ScrubMode::Paused {
+            return Ok(match time::timeout(PAUSE_POLL_INTERVAL, self.commands.recv()).await {
+                Ok(Some(ScrubCommand::Resume)) => {
+                    info!("Scrub worker resumed.");
+                    self.mode = ScrubMode::Running;
+                    WorkerState::Idle
+                }
+                Ok(Some(ScrubCommand::Pause)) => WorkerState::Idle,
+                Ok(Some(ScrubCommand::Cancel)) | Ok(None) => {
+                    info!("Scrub worker cancelled.");
+                    WorkerState::Done
+                }
+                Err(_elapsed) => WorkerState::Idle,
+            });
+        }
+
+        let start = Instant::now();
+        let progressed = self.reconcile_next_batch().await?;
+        let rest = start.elapsed() * self.tranquility;
+        let rest = if progressed { rest } else { rest.max(MIN_IDLE_BACKOFF) };
+
+        tokio::select! {
+            cmd = self.commands.recv() => match cmd {
+                Some(ScrubCommand::Pause) => {
+                    info!("Scrub worker paused.");
+                    self.mode = ScrubMode::Paused;
+                }
+                Some(ScrubCommand::Resume) => {}
+                Some(ScrubCommand::Cancel) | None => {
+                    info!("Scrub worker cancelled.");
+                    return Ok(WorkerState::Done);
+                }
+            },
+            _ = time::sleep(rest) => {}
+        }
+
+        Ok(if progressed {
+            WorkerState::Active
+        } else {
+            WorkerState::Idle
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bucket_totals(data: MetricsData, scroll: ScrollDirections) -> BucketTotals {
+        BucketTotals {
+            bucket: "2026-07-29".into(),
+            data,
+            scroll,
+        }
+    }
+
+    #[test]
+    fn missing_totals_reports_the_local_surplus() {
+        let local = bucket_totals(
+            MetricsData {
+                keypresses: 10,
+                mouse_clicks: 5,
+                scroll_steps: 3,
+                mouse_distance_in: 2.0,
+            },
+            ScrollDirections {
+                up: 3,
+                down: 0,
+                left: 0,
+                right: 0,
+            },
+        );
+        let remote = bucket_totals(
+            MetricsData {
+                keypresses: 4,
+                mouse_clicks: 5,
+                scroll_steps: 3,
+                mouse_distance_in: 1.5,
+            },
+            ScrollDirections {
+                up: 1,
+                down: 0,
+                left: 0,
+                right: 0,
+            },
+        );
+
+        let (missing_data, missing_scroll) =
+            missing_totals(&local, &remote).expect("remote is behind local");
+        assert_eq!(missing_data.keypresses, 6);
+        assert_eq!(missing_data.mouse_clicks, 0);
+        assert_eq!(missing_data.scroll_steps, 0);
+        assert!((missing_data.mouse_distance_in - 0.5).abs() < f64::EPSILON);
+        assert_eq!(missing_scroll.up, 2);
+        assert_eq!(missing_scroll.down, 0);
+    }
+
+    #[test]
+    fn missing_totals_catches_direction_only_drift() {
+        // Rolled-up totals agree, but remote is missing part of the
+        // direction split — this must still count as drift, not be zeroed
+        // out by the repair.
+        let local = bucket_totals(
+            MetricsData {
+                keypresses: 0,
+                mouse_clicks: 0,
+                scroll_steps: 10,
+                mouse_distance_in: 0.0,
+            },
+            ScrollDirections {
+                up: 6,
+                down: 4,
+                left: 0,
+                right: 0,
+            },
+        );
+        let remote = bucket_totals(
+            MetricsData {
+                keypresses: 0,
+                mouse_clicks: 0,
+                scroll_steps: 10,
+                mouse_distance_in: 0.0,
+            },
+            ScrollDirections {
+                up: 6,
+                down: 0,
+                left: 0,
+                right: 0,
+            },
+        );
+
+        let (_, missing_scroll) = missing_totals(&local, &remote).expect("direction drift");
+        assert_eq!(missing_scroll.down, 4);
+    }
+
+    #[test]
+    fn missing_totals_is_none_when_remote_is_caught_up() {
+        let local = bucket_totals(
+            MetricsData {
+                keypresses: 4,
+                mouse_clicks: 5,
+                scroll_steps: 3,
+                mouse_distance_in: 1.5,
+            },
+            ScrollDirections {
+                up: 1,
+                down: 2,
+                left: 0,
+                right: 0,
+            },
+        );
+        let remote = local.clone();
+
+        assert!(missing_totals(&local, &remote).is_none());
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
 use tokio::sync::Mutex;
 
@@ -6,23 +7,64 @@ pub struct IntervalMetrics {
     pub keypresses: AtomicUsize,
     pub mouse_clicks: AtomicUsize,
     pub scroll_steps: AtomicUsize,
+    pub scroll_up: AtomicUsize,
+    pub scroll_down: AtomicUsize,
+    pub scroll_left: AtomicUsize,
+    pub scroll_right: AtomicUsize,
     pub mouse_distance_in: Mutex<f64>,
+    pub key_counts: Mutex<HashMap<String, usize>>,
+    pub button_counts: Mutex<HashMap<String, usize>>,
+}
+
+/// A consistent snapshot of everything accumulated during one interval,
+/// returned by [`IntervalMetrics::reset`] and folded into [`TotalMetrics`].
+#[derive(Debug, Clone, Default)]
+pub struct IntervalSnapshot {
+    pub keypresses: usize,
+    pub mouse_clicks: usize,
+    pub scroll_steps: usize,
+    pub scroll_up: usize,
+    pub scroll_down: usize,
+    pub scroll_left: usize,
+    pub scroll_right: usize,
+    pub mouse_distance_in: f64,
+    pub key_counts: HashMap<String, usize>,
+    pub button_counts: HashMap<String, usize>,
 }
 
 impl IntervalMetrics {
     /// Resets the interval metrics to zero, returning the values captured during the interval.
-    pub async fn reset(&self) -> (usize, usize, usize, f64) {
-        let keys = self.keypresses.swap(0, Ordering::Relaxed);
-        let clicks = self.mouse_clicks.swap(0, Ordering::Relaxed);
-        let scrolls = self.scroll_steps.swap(0, Ordering::Relaxed);
+    pub async fn reset(&self) -> IntervalSnapshot {
+        let keypresses = self.keypresses.swap(0, Ordering::Relaxed);
+        let mouse_clicks = self.mouse_clicks.swap(0, Ordering::Relaxed);
+        let scroll_steps = self.scroll_steps.swap(0, Ordering::Relaxed);
+        let scroll_up = self.scroll_up.swap(0, Ordering::Relaxed);
+        let scroll_down = self.scroll_down.swap(0, Ordering::Relaxed);
+        let scroll_left = self.scroll_left.swap(0, Ordering::Relaxed);
+        let scroll_right = self.scroll_right.swap(0, Ordering::Relaxed);
 
-        let distance = {
+        let mouse_distance_in = {
             let mut dist_lock = self.mouse_distance_in.lock().await;
             let current_dist = *dist_lock;
             *dist_lock = 0.0;
             current_dist
         };
-        (keys, clicks, scrolls, distance)
+
+        let key_counts = std::mem::take(&mut *self.key_counts.lock().await);
+        let button_counts = std::mem::take(&mut *self.button_counts.lock().await);
+
+        IntervalSnapshot {
+            keypresses,
+            mouse_clicks,
+            scroll_steps,
+            scroll_up,
+            scroll_down,
+            scroll_left,
+            scroll_right,
+            mouse_distance_in,
+            key_counts,
+            button_counts,
+        }
     }
 }
 
@@ -32,17 +74,34 @@ pub struct TotalMetrics {
     pub mouse_clicks: AtomicUsize,
     pub scroll_steps: AtomicUsize,
     pub mouse_distance_in: Mutex<f64>,
+    pub key_counts: Mutex<HashMap<String, usize>>,
+    pub button_counts: Mutex<HashMap<String, usize>>,
 }
 
 impl TotalMetrics {
     /// Adds the values from a completed interval to the running totals.
-    pub async fn add_interval(&self, keys: usize, clicks: usize, scrolls: usize, distance: f64) {
-        self.keypresses.fetch_add(keys, Ordering::Relaxed);
-        self.mouse_clicks.fetch_add(clicks, Ordering::Relaxed);
-        self.scroll_steps.fetch_add(scrolls, Ordering::Relaxed);
-        if distance > 0.0 {
+    pub async fn add_interval(&self, snapshot: &IntervalSnapshot) {
+        self.keypresses
+            .fetch_add(snapshot.keypresses, Ordering::Relaxed);
+        self.mouse_clicks
+            .fetch_add(snapshot.mouse_clicks, Ordering::Relaxed);
+        self.scroll_steps
+            .fetch_add(snapshot.scroll_steps, Ordering::Relaxed);
+        if snapshot.mouse_distance_in > 0.0 {
             let mut total_dist_lock = self.mouse_distance_in.lock().await;
-            *total_dist_lock += distance;
+            *total_dist_lock += snapshot.mouse_distance_in;
+        }
+        if !snapshot.key_counts.is_empty() {
+            let mut totals = self.key_counts.lock().await;
+            for (key, count) in &snapshot.key_counts {
+                *totals.entry(key.clone()).or_insert(0) += count;
+            }
+        }
+        if !snapshot.button_counts.is_empty() {
+            let mut totals = self.button_counts.lock().await;
+            for (button, count) in &snapshot.button_counts {
+                *totals.entry(button.clone()).or_insert(0) += count;
+            }
         }
     }
 }
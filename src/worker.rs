@@ -0,0 +1,276 @@
+use crate::error::{AppError, Result};
+use async_trait::async_trait;
+use futures::FutureExt;
+use std::any::Any;
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn, Instrument};
+
+/// Initial delay before restarting a worker whose `step` returned `Err`.
+const RESTART_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// Upper bound on the restart backoff delay.
+const RESTART_MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Number of consecutive successful steps required before the backoff delay
+/// resets to its initial value.
+const CLEAN_RUN_RESET_THRESHOLD: u32 = 10;
+/// Once a worker has failed more than this many times in a row (any
+/// successful step resets the count), the failure is treated as fatal and
+/// the daemon shuts down.
+const MAX_CONSECUTIVE_FAILURES: u32 = 5;
+
+/// Outcome of a single [`Worker::step`] call. `BackgroundManager` uses this
+/// to decide whether the worker is making progress, waiting idle for more
+/// work, or has finished for good.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerState {
+    Active,
+    Idle,
+    Done,
+}
+
+/// A unit of background work that [`BackgroundManager`] can spawn and
+/// supervise. Implementors advance by one `step` at a time rather than
+/// owning their own loop, so the manager can wrap every iteration with
+/// shutdown handling and status bookkeeping.
+#[async_trait]
+pub trait Worker: Send + 'static {
+    /// A short, stable name used to identify this worker in status reports.
+    fn name(&self) -> &str;
+
+    /// Performs one unit of work and reports what happened.
+    async fn step(&mut self) -> Result<WorkerState>;
+
+    /// Called with the error returned by a failed `step`, before the worker
+    /// is marked `Dead`. The default just logs; override for worker-specific
+    /// cleanup or recovery.
+    async fn on_error(&mut self, err: &AppError) {
+        error!("Worker '{}' step failed: {}", self.name(), err);
+    }
+}
+
+/// Current lifecycle state of a registered worker, as reported by
+/// [`BackgroundManager::report`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerStatus {
+    Active,
+    Idle,
+    Dead,
+}
+
+/// A point-in-time snapshot of one worker's status, iteration count, and
+/// last error, as returned by [`BackgroundManager::report`].
+#[derive(Debug, Clone)]
+pub struct WorkerReport {
+    pub name: String,
+    pub status: WorkerStatus,
+    pub iterations: u64,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct WorkerEntry {
+    status: Option<WorkerStatus>,
+    iterations: u64,
+    last_error: Option<String>,
+}
+
+/// Runs `worker.step()` with `catch_unwind`, turning a panic into an
+/// `AppError::WorkerPanic` so a bug inside one worker's `step` (an index
+/// panic, a poisoned lock, ...) is treated the same as a returned `Err`
+/// instead of silently unwinding the whole spawned task.
+async fn panic_safe_step<W: Worker + ?Sized>(worker: &mut W) -> Result<WorkerState> {
+    AssertUnwindSafe(worker.step())
+        .catch_unwind()
+        .await
+        .unwrap_or_else(|payload| Err(AppError::WorkerPanic(panic_message(payload))))
+}
+
+/// Extracts a human-readable message from a caught panic payload, falling
+/// back to a generic description for payloads that are neither `&str` nor
+/// `String` (the two types `std::panic!` actually produces).
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "worker panicked with a non-string payload".to_string()
+    }
+}
+
+/// Owns a registry of spawned [`Worker`]s and tracks each one's state,
+/// iteration count, and last error, so the rest of the app (or an operator)
+/// can tell at a glance whether a background loop is alive, idle, or dead.
+#[derive(Clone, Default)]
+pub struct BackgroundManager {
+    entries: Arc<RwLock<HashMap<String, WorkerEntry>>>,
+}
+
+impl BackgroundManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spawns `worker` on its own task, repeatedly calling `step()` until it
+    /// returns `Done` or `shutdown_tx` fires. A `step()` that returns `Err`
+    /// (or panics — caught via `catch_unwind` and converted to
+    /// `AppError::WorkerPanic`) is retried with exponential backoff
+    /// (`RESTART_INITIAL_BACKOFF` up to
+    /// `RESTART_MAX_BACKOFF`), with the backoff reset after
+    /// `CLEAN_RUN_RESET_THRESHOLD` consecutive successful steps. If a
+    /// worker fails more than `MAX_CONSECUTIVE_FAILURES` times in a row
+    /// without a successful step in between, the failure is treated as
+    /// fatal: the worker
+    /// stops for good and broadcasts on `shutdown_tx` so the rest of the
+    /// daemon tears down with it.
+    pub async fn spawn<W: Worker>(
+        &self,
+        mut worker: W,
+        shutdown_tx: broadcast::Sender<()>,
+    ) -> JoinHandle<()> {
+        let name = worker.name().to_string();
+        self.entries
+            .write()
+            .await
+            .insert(name.clone(), WorkerEntry::default());
+
+        let entries = self.entries.clone();
+        let span = tracing::info_span!("worker", worker = %name);
+        tokio::spawn(async move {
+            let mut shutdown_rx = shutdown_tx.subscribe();
+            let mut backoff = RESTART_INITIAL_BACKOFF;
+            let mut consecutive_failures: u32 = 0;
+            let mut consecutive_successes: u32 = 0;
+
+            loop {
+                tokio::select! {
+                    biased;
+                    _ = shutdown_rx.recv() => {
+                        debug!("Worker '{}' received shutdown signal", name);
+                        break;
+                    }
+                    result = panic_safe_step(&mut worker) => {
+                        match result {
+                            Ok(WorkerState::Done) => {
+                                info!("Worker '{}' finished.", name);
+                                if let Some(entry) = entries.write().await.get_mut(&name) {
+                                    entry.status = Some(WorkerStatus::Dead);
+                                }
+                                break;
+                            }
+                            Ok(state) => {
+                                consecutive_failures = 0;
+                                consecutive_successes = consecutive_successes.saturating_add(1);
+                                if consecutive_successes >= CLEAN_RUN_RESET_THRESHOLD {
+                                    backoff = RESTART_INITIAL_BACKOFF;
+                                }
+
+                                let status = match state {
+                                    WorkerState::Active => WorkerStatus::Active,
+                                    WorkerState::Idle => WorkerStatus::Idle,
+                                    WorkerState::Done => unreachable!(),
+                                };
+                                if let Some(entry) = entries.write().await.get_mut(&name) {
+                                    entry.status = Some(status);
+                                    entry.iterations += 1;
+                                }
+                            }
+                            Err(e) => {
+                                worker.on_error(&e).await;
+                                consecutive_successes = 0;
+                                consecutive_failures += 1;
+                                if let Some(entry) = entries.write().await.get_mut(&name) {
+                                    entry.status = Some(WorkerStatus::Dead);
+                                    entry.last_error = Some(e.to_string());
+                                }
+
+                                if consecutive_failures > MAX_CONSECUTIVE_FAILURES {
+                                    error!(
+                                        "Worker '{}' exceeded {} consecutive failures, triggering daemon shutdown.",
+                                        name, MAX_CONSECUTIVE_FAILURES
+                                    );
+                                    let _ = shutdown_tx.send(());
+                                    break;
+                                }
+
+                                warn!(
+                                    "Worker '{}' failed ({}/{} consecutive), restarting in {:?}: {}",
+                                    name, consecutive_failures, MAX_CONSECUTIVE_FAILURES, backoff, e
+                                );
+                                tokio::time::sleep(backoff).await;
+                                backoff = (backoff * 2).min(RESTART_MAX_BACKOFF);
+                            }
+                        }
+                    }
+                }
+            }
+        }.instrument(span))
+    }
+
+    /// Returns a snapshot of every registered worker's status, iteration
+    /// count, and last error.
+    pub async fn report(&self) -> Vec<WorkerReport> {
+        self.entries
+            .read()
+            .await
+            .iter()
+            .map(|(name, entry)| WorkerReport {
+                name: name.clone(),
+                status: entry.status.unwrap_or(WorkerStatus::Idle),
+                iterations: entry.iterations,
+                last_error: entry.last_error.clone(),
+            })
+            .collect()
+    }
+
+    /// Logs a snapshot of every worker's status via `tracing`. Intended to be
+    /// triggered by a signal (e.g. `SIGUSR1`) so an operator can check the
+    /// health of background loops without attaching a debugger.
+    pub async fn log_status(&self) {
+        let report = self.report().await;
+        info!("Background worker status ({} worker(s)):", report.len());
+        for worker in &report {
+            match &worker.last_error {
+                Some(err) => info!(
+                    "  {} — {:?}, {} iteration(s), last error: {}",
+                    worker.name, worker.status, worker.iterations, err
+                ),
+                None => info!(
+                    "  {} — {:?}, {} iteration(s)",
+                    worker.name, worker.status, worker.iterations
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct PanickingWorker;
+
+    #[async_trait]
+    impl Worker for PanickingWorker {
+        fn name(&self) -> &str {
+            "panicking"
+        }
+
+        async fn step(&mut self) -> Result<WorkerState> {
+            panic!("boom");
+        }
+    }
+
+    #[tokio::test]
+    async fn panic_safe_step_converts_a_panic_into_worker_panic_error() {
+        let mut worker = PanickingWorker;
+
+        let err = panic_safe_step(&mut worker).await.unwrap_err();
+
+        assert!(matches!(err, AppError::WorkerPanic(ref msg) if msg == "boom"));
+    }
+}